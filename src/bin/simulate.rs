@@ -0,0 +1,57 @@
+use cards_online::bot::BotKind;
+use cards_online::lobby::RuleOptions;
+use cards_online::simulation::{self, SimulationConfig};
+use std::env;
+use std::process;
+
+const USAGE: &str =
+    "usage: simulate [--games N] [--seed N] [--rounds N] [--expansion] [--fourth-suit] [--scale-rounds] <random|heuristic>...";
+
+fn main() {
+    let mut config = SimulationConfig {
+        games: 1000,
+        seats: Vec::new(),
+        rules: RuleOptions::new(),
+        seed: rand::random(),
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = || -> u64 {
+            args.next()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_else(|| {
+                    eprintln!("{} expects a number\n{}", arg, USAGE);
+                    process::exit(2);
+                })
+        };
+        match arg.as_str() {
+            "--games" => config.games = number() as usize,
+            "--seed" => config.seed = number(),
+            "--rounds" => config.rules.rounds = number() as i32,
+            "--expansion" => config.rules.expansion = true,
+            "--fourth-suit" => config.rules.fourth_suit = true,
+            "--scale-rounds" => config.rules.scale_rounds = true,
+            kind => match kind.parse::<BotKind>() {
+                Ok(kind) => config.seats.push(kind),
+                Err(error) => {
+                    eprintln!("{}\n{}", error, USAGE);
+                    process::exit(2);
+                }
+            },
+        }
+    }
+    if config.seats.len() < 2 {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+
+    println!("Seed {}", config.seed);
+    match simulation::run(&config) {
+        Ok(report) => print!("{}", report),
+        Err(error) => {
+            eprintln!("simulation failed: {}", error);
+            process::exit(1);
+        }
+    }
+}
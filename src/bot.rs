@@ -0,0 +1,319 @@
+use rand::{seq::IndexedRandom, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{
+    error::GameError,
+    game::{self, CardEffect, CardType},
+    lifecycle::{Game, GamePhase},
+    online_board,
+    view::PlayerView,
+};
+
+/// How a bot decides its bid and its cards, seeing only what a human in
+/// the same seat would see.
+pub trait Strategy: fmt::Debug + Send + Sync {
+    fn bid(&self, view: &PlayerView, rng: &mut dyn RngCore) -> i32;
+
+    /// Index in `view.hand` of the card to play, with the MarySue choice.
+    fn play(&self, view: &PlayerView, rng: &mut dyn RngCore) -> (usize, Option<CardEffect>);
+}
+
+fn legal_plays(view: &PlayerView) -> Vec<usize> {
+    online_board::legal_plays(&view.table.river, &view.hand)
+}
+
+/// Bids and plays anything legal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomBot;
+
+impl Strategy for RandomBot {
+    fn bid(&self, view: &PlayerView, rng: &mut dyn RngCore) -> i32 {
+        rng.random_range(0..=view.table.round)
+    }
+
+    fn play(&self, view: &PlayerView, rng: &mut dyn RngCore) -> (usize, Option<CardEffect>) {
+        let card_index = *legal_plays(view).choose(rng).unwrap_or(&0);
+        let choice = if rng.random_bool(0.5) {
+            CardEffect::Pirate
+        } else {
+            CardEffect::Flag
+        };
+        (card_index, Some(choice))
+    }
+}
+
+/// Bids the cards likely to take a trick, then plays to win exactly that
+/// many: cheapest winning card while short, strongest losing card after.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicBot;
+
+/// Rough order of how hard a card is to beat in a deck numbered up to
+/// `highest_value`: suits, then Skulls, then the specials above them.
+fn strength(card: &CardType, highest_value: i32) -> i32 {
+    match card {
+        CardType::Flag(_) | CardType::Kraken(_) | CardType::WhiteWhale(_) | CardType::Loot(_) => 0,
+        CardType::Color(card) => card.value,
+        CardType::Skull(card) => highest_value + card.value,
+        CardType::Mermaid(_) => 2 * highest_value + 1,
+        CardType::Pirate(_) | CardType::MarySue(_) => 2 * highest_value + 2,
+        CardType::SkullKing(_) => 2 * highest_value + 3,
+    }
+}
+
+/// Whether `card` should take a trick in a deck numbered up to
+/// `highest_value`: the top two of a suit, the top five Skulls.
+fn is_likely_winner(card: &CardType, highest_value: i32) -> bool {
+    match card {
+        CardType::Color(card) => card.value >= highest_value - 1,
+        CardType::Skull(card) => card.value >= highest_value - 4,
+        CardType::Flag(_) | CardType::Kraken(_) | CardType::WhiteWhale(_) | CardType::Loot(_) => {
+            false
+        }
+        _ => true,
+    }
+}
+
+/// Whether `card` would take the trick if nobody played after it.
+fn wins_now(view: &PlayerView, card: CardType) -> bool {
+    let mut trick: Vec<CardType> = view
+        .table
+        .river
+        .iter()
+        .map(|played_card| played_card.card().card_type)
+        .collect();
+    trick.push(card);
+    game::trick_winner(&trick) == Some(trick.len() - 1)
+}
+
+fn with_choice(card: CardType, choice: CardEffect) -> CardType {
+    match card {
+        CardType::MarySue(mut mary_sue) => {
+            mary_sue.choice = Some(choice);
+            CardType::MarySue(mary_sue)
+        }
+        card => card,
+    }
+}
+
+impl Strategy for HeuristicBot {
+    fn bid(&self, view: &PlayerView, _rng: &mut dyn RngCore) -> i32 {
+        let winners = view
+            .hand
+            .iter()
+            .filter(|card| is_likely_winner(&card.card_type, view.table.highest_value))
+            .count() as i32;
+        winners.min(view.table.round)
+    }
+
+    fn play(&self, view: &PlayerView, _rng: &mut dyn RngCore) -> (usize, Option<CardEffect>) {
+        let seat = view
+            .table
+            .seats
+            .iter()
+            .find(|seat| seat.player_id == view.player_id);
+        let tricks_won = seat.map_or(0, |seat| seat.tricks_won);
+        let bid = view
+            .table
+            .bids
+            .iter()
+            .flatten()
+            .find(|bid| bid.player_id == view.player_id)
+            .map_or(0, |bid| bid.bid);
+        let wants_tricks = tricks_won < bid;
+        let choice = if wants_tricks {
+            CardEffect::Pirate
+        } else {
+            CardEffect::Flag
+        };
+
+        let mut candidates = legal_plays(view);
+        candidates.sort_by_key(|&index| {
+            strength(
+                &with_choice(view.hand[index].card_type, choice),
+                view.table.highest_value,
+            )
+        });
+        let winning = candidates
+            .iter()
+            .copied()
+            .filter(|&index| wins_now(view, with_choice(view.hand[index].card_type, choice)));
+        let card_index = if wants_tricks {
+            // cheapest card taking the trick, or dump the weakest one
+            winning.clone().next().or(candidates.first().copied())
+        } else {
+            // strongest card still losing, or the weakest if all of them win
+            let winners: Vec<usize> = winning.collect();
+            candidates
+                .iter()
+                .rev()
+                .find(|index| !winners.contains(index))
+                .or(candidates.first())
+                .copied()
+        };
+        (card_index.unwrap_or(0), Some(choice))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BotKind {
+    Random,
+    Heuristic,
+}
+
+impl FromStr for BotKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "random" => Ok(BotKind::Random),
+            "heuristic" => Ok(BotKind::Heuristic),
+            other => Err(format!("Unknown bot {}, use random or heuristic", other)),
+        }
+    }
+}
+
+impl BotKind {
+    pub fn strategy(&self) -> Box<dyn Strategy> {
+        match self {
+            BotKind::Random => Box::new(RandomBot),
+            BotKind::Heuristic => Box::new(HeuristicBot),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotSeat {
+    pub player_id: i32,
+    pub kind: BotKind,
+}
+
+impl Game {
+    /// Fills a seat with a bot playing `kind`.
+    pub fn add_bot(&mut self, kind: BotKind) -> Result<i32, GameError> {
+        let name = format!("{:?} bot {}", kind, self.next_player_id + 1);
+        self.seat_player(name, Some(kind))
+    }
+
+    fn bot_kind(&self, player_id: i32) -> Option<BotKind> {
+        self.bots
+            .iter()
+            .find(|bot| bot.player_id == player_id)
+            .map(|bot| bot.kind)
+    }
+
+    /// Lets every bot whose turn it is act, until a human has to.
+    pub fn run_bots(&mut self, rng: &mut dyn RngCore) -> Result<(), GameError> {
+        loop {
+            match self.phase {
+                GamePhase::Bidding => {
+                    let waiting = self.table.seats.iter().find_map(|seat| {
+                        let player_id = seat.player.player_id;
+                        match (seat.bid, self.bot_kind(player_id)) {
+                            (None, Some(kind)) => Some((player_id, kind)),
+                            _ => None,
+                        }
+                    });
+                    let Some((player_id, kind)) = waiting else {
+                        return Ok(());
+                    };
+                    let bid = kind.strategy().bid(&self.player_view(player_id)?, rng);
+                    self.bid(player_id, bid)?;
+                }
+                GamePhase::Playing => {
+                    let player_id = self.table.seats[self.current_seat()].player.player_id;
+                    let Some(kind) = self.bot_kind(player_id) else {
+                        return Ok(());
+                    };
+                    let (card_index, choice) =
+                        kind.strategy().play(&self.player_view(player_id)?, rng);
+                    self.play(player_id, card_index, choice)?;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_bots_play_whole_rounds() {
+    let mut game = Game::new();
+    let human = game.add_player("anne".to_string()).unwrap();
+    game.add_bot(BotKind::Random).unwrap();
+    game.add_bot(BotKind::Heuristic).unwrap();
+    game.start().unwrap();
+    let mut rng = rand::rng();
+
+    game.run_bots(&mut rng).unwrap();
+    assert_eq!(game.phase, GamePhase::Bidding);
+    game.bid(human, 0).unwrap();
+    game.play_out_round(CardEffect::Flag);
+    assert_eq!(game.phase, GamePhase::RoundScoring);
+}
+
+#[cfg(test)]
+#[test]
+fn test_heuristic_bot_bids_its_winners() {
+    use crate::{
+        game::{
+            with_ids, CardColor, ColorCard, DeckConfig, MermaidCard, SkullCard, SkullKingCard,
+            WhiteFlagCard,
+        },
+        lobby::{RuleOptions, TableSettings},
+    };
+
+    let mut game = Game::new();
+    let bot = game.add_bot(BotKind::Heuristic).unwrap();
+    game.add_player("anne".to_string()).unwrap();
+    game.start().unwrap();
+    let seat = game.seat_of(bot).unwrap();
+    game.table.round = 3;
+    game.table.seats[seat].hand = with_ids(vec![
+        CardType::SkullKing(SkullKingCard {}),
+        CardType::Color(ColorCard {
+            color: CardColor::Red,
+            value: 14,
+        }),
+        CardType::Flag(WhiteFlagCard {}),
+    ]);
+    let view = game.player_view(bot).unwrap();
+    assert_eq!(HeuristicBot.bid(&view, &mut rand::rng()), 2);
+    assert_eq!(strength(&view.hand[2].card_type, 14), 0);
+
+    // a 14 is no longer a winner when the suits run to 20
+    let mut game = Game::with_settings(TableSettings {
+        rules: RuleOptions {
+            deck: Some(DeckConfig {
+                max_value: 20,
+                ..DeckConfig::new()
+            }),
+            ..RuleOptions::new()
+        },
+        ..TableSettings::new()
+    })
+    .unwrap();
+    let bot = game.add_bot(BotKind::Heuristic).unwrap();
+    game.add_player("anne".to_string()).unwrap();
+    game.start().unwrap();
+    let seat = game.seat_of(bot).unwrap();
+    game.table.round = 2;
+    game.table.seats[seat].hand = with_ids(
+        [14, 19]
+            .map(|value| {
+                CardType::Color(ColorCard {
+                    color: CardColor::Red,
+                    value,
+                })
+            })
+            .to_vec(),
+    );
+    let view = game.player_view(bot).unwrap();
+    assert_eq!(HeuristicBot.bid(&view, &mut rand::rng()), 1);
+    // and a Skull 20 still ranks below the Mermaid
+    assert!(
+        strength(&CardType::Skull(SkullCard { value: 20 }), 20)
+            < strength(&CardType::Mermaid(MermaidCard {}), 20)
+    );
+}
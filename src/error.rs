@@ -0,0 +1,62 @@
+use std::fmt;
+
+use crate::game::CardColor;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameError {
+    CardNotInHand,
+    IllegalCard { lead: CardColor },
+    UnknownPlayer,
+    InvalidBid { max: i32 },
+    AlreadyBid,
+    NotYourTurn,
+    WrongPhase,
+    NotEnoughPlayers,
+    MissingChoice,
+    UnknownTable,
+    TableFull,
+    NotHost,
+    InvalidSettings { reason: &'static str },
+    SettingTooHigh { setting: &'static str, max: i32 },
+    Unauthorized,
+    DeckExhausted,
+    NoChoice,
+    InvalidCard,
+    CardsNotConserved,
+    BotFailed,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::CardNotInHand => write!(f, "this card is not in your hand"),
+            GameError::IllegalCard { lead } => {
+                write!(f, "you must follow the {:?} lead while you hold it", lead)
+            }
+            GameError::UnknownPlayer => write!(f, "no such player at this table"),
+            GameError::InvalidBid { max } => write!(f, "bid must be between 0 and {}", max),
+            GameError::AlreadyBid => write!(f, "you already bid this round"),
+            GameError::NotYourTurn => write!(f, "it is not your turn"),
+            GameError::WrongPhase => write!(f, "this is not possible at this point of the game"),
+            GameError::NotEnoughPlayers => write!(f, "not enough players to start"),
+            GameError::UnknownTable => write!(f, "no such table"),
+            GameError::TableFull => write!(f, "this table is full"),
+            GameError::NotHost => write!(f, "only the host can do this"),
+            GameError::InvalidSettings { reason } => write!(f, "invalid settings: {}", reason),
+            GameError::SettingTooHigh { setting, max } => {
+                write!(f, "invalid settings: {} cannot go above {}", setting, max)
+            }
+            GameError::Unauthorized => write!(f, "missing or unknown session token"),
+            GameError::DeckExhausted => write!(f, "not enough cards left in the deck"),
+            GameError::NoChoice => write!(f, "only MarySue can be played as a Pirate or a Flag"),
+            GameError::InvalidCard => write!(f, "numbered cards need a number and a color"),
+            GameError::CardsNotConserved => write!(f, "cards were lost or duplicated"),
+            GameError::BotFailed => write!(f, "a bot at this table could not play"),
+            GameError::MissingChoice => {
+                write!(f, "choose whether MarySue is played as a Pirate or a Flag")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
@@ -0,0 +1,18 @@
+use serde::Serialize;
+
+use crate::{game::PlayCard, scoring::Scoreboard};
+
+/// Public notifications pushed to everyone watching a table.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event")]
+pub enum TableEvent {
+    PlayerJoined { player_id: i32, name: String },
+    PlayerLeft { player_id: i32 },
+    RoundStarted { round: i32 },
+    BidsRevealed { bids: Vec<(i32, i32)> },
+    CardPlayed { player_id: i32, card: PlayCard },
+    TrickWon { player_id: i32 },
+    TrickDestroyed { next_player_id: i32 },
+    RoundScored { round: i32, points: Vec<(i32, i32)> },
+    GameOver { scoreboard: Scoreboard },
+}
@@ -2,10 +2,13 @@ use std::any::Any;
 use std::fmt;
 use std::vec;
 
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, PartialEq, Debug)]
-enum CardTypeName {
+use crate::error::GameError;
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum CardTypeName {
     Color,
     Skull,
     Flag,
@@ -13,9 +16,13 @@ enum CardTypeName {
     Pirate,
     MarySue,
     SkullKing,
+    Kraken,
+    WhiteWhale,
+    Loot,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum CardType {
     Color(ColorCard),
     Skull(SkullCard),
@@ -24,6 +31,9 @@ pub enum CardType {
     Pirate(PirateCard),
     MarySue(MarySueCard),
     SkullKing(SkullKingCard),
+    Kraken(KrakenCard),
+    WhiteWhale(WhiteWhaleCard),
+    Loot(LootCard),
 }
 
 impl fmt::Display for CardType {
@@ -36,21 +46,65 @@ impl fmt::Display for CardType {
             CardType::Pirate(card) => write!(f, "{}", card),
             CardType::MarySue(card) => write!(f, "{}", card),
             CardType::SkullKing(card) => write!(f, "{}", card),
+            CardType::Kraken(card) => write!(f, "{}", card),
+            CardType::WhiteWhale(card) => write!(f, "{}", card),
+            CardType::Loot(card) => write!(f, "{}", card),
         }
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+impl CardType {
+    pub fn card_type_name(&self) -> CardTypeName {
+        match self {
+            CardType::Color(card) => card.card_type(),
+            CardType::Skull(card) => card.card_type(),
+            CardType::Flag(card) => card.card_type(),
+            CardType::Mermaid(card) => card.card_type(),
+            CardType::Pirate(card) => card.card_type(),
+            CardType::MarySue(card) => card.card_type(),
+            CardType::SkullKing(card) => card.card_type(),
+            CardType::Kraken(card) => card.card_type(),
+            CardType::WhiteWhale(card) => card.card_type(),
+            CardType::Loot(card) => card.card_type(),
+        }
+    }
+
+    pub fn is_mary_sue(&self) -> bool {
+        matches!(self, CardType::MarySue(_))
+    }
+
+    /// Suit this card has to follow, `None` for specials that can be played
+    /// at any time.
+    pub fn suit(&self) -> Option<CardColor> {
+        match self {
+            CardType::Color(card) => Some(card.card_color()),
+            CardType::Skull(card) => Some(card.card_color()),
+            _ => None,
+        }
+    }
+
+    /// Number printed on the card, `None` for specials.
+    pub fn value(&self) -> Option<i32> {
+        match self {
+            CardType::Color(card) => Some(card.value),
+            CardType::Skull(card) => Some(card.value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum CardEffect {
     Pirate,
     Flag,
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum CardColor {
     Red,
     Blue,
     Green,
+    Yellow,
     Black,
     Brown,
     Pink,
@@ -58,44 +112,70 @@ pub enum CardColor {
     White,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct ColorCard {
     pub color: CardColor,
     pub value: i32,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct SkullCard {
     pub value: i32,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct MermaidCard {}
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct PirateCard {}
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct MarySueCard {
     pub choice: Option<CardEffect>,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct WhiteFlagCard {}
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct SkullKingCard {}
 
-// type PlayCard = Box<dyn Card>;
-pub type PlayCard = CardType;
+/// Expansion: destroys the trick, nobody takes it.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct KrakenCard {}
+
+/// Expansion: specials lose their power and the highest number wins.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct WhiteWhaleCard {}
+
+/// Expansion: an escape that allies its player with the trick winner.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct LootCard {}
+
+pub type CardId = u32;
+
+/// One physical card of the deck. Identical cards, like the five Pirates,
+/// are told apart by the `id` given when the deck is created.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct PlayCard {
+    pub id: CardId,
+    #[serde(flatten)]
+    pub card_type: CardType,
+}
+
+impl fmt::Display for PlayCard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{} {}", self.id, self.card_type)
+    }
+}
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Deck {
     pub cards: Vec<PlayCard>,
 }
 
 impl Deck {
-    pub fn new() -> Self {
+    pub fn new() -> Result<Self, GameError> {
         create_default_deck()
     }
 
@@ -103,9 +183,15 @@ impl Deck {
         Deck { cards: Vec::new() }
     }
 
-    pub fn shuffle(&mut self) {
-        let mut rng = rand::rng();
-        self.cards.shuffle(&mut rng);
+    pub fn shuffle(&mut self, rng: &mut impl Rng) {
+        self.cards.shuffle(rng);
+    }
+
+    /// Adds a card while the deck is being created, numbering it after the
+    /// cards already in.
+    fn push_new(&mut self, card_type: CardType) {
+        let id = self.cards.len() as CardId;
+        self.cards.push(PlayCard { id, card_type });
     }
 }
 
@@ -116,7 +202,7 @@ pub trait Card: fmt::Display + fmt::Debug + Sync + Send + Clone {
     fn card_value(&self) -> Option<i32>;
     fn is_card_special(&self) -> bool;
     fn is_card_atout(&self) -> bool;
-    fn set_card_effect(&mut self, _player_choice: CardEffect);
+    fn set_card_effect(&mut self, _player_choice: CardEffect) -> Result<(), GameError>;
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
@@ -177,14 +263,14 @@ impl Card for SkullCard {
         true
     }
 
-    fn set_card_effect(&mut self, _player_choice: CardEffect) {
-        panic!("Cannot set card type on SkullCard");
+    fn set_card_effect(&mut self, _player_choice: CardEffect) -> Result<(), GameError> {
+        Err(GameError::NoChoice)
     }
 }
 
 impl fmt::Display for SkullCard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} Skull\n", self.value)
+        writeln!(f, "{} Skull", self.value)
     }
 }
 
@@ -213,14 +299,14 @@ impl Card for ColorCard {
         false
     }
 
-    fn set_card_effect(&mut self, _player_choice: CardEffect) {
-        panic!("Cannot set card type on ColorCard");
+    fn set_card_effect(&mut self, _player_choice: CardEffect) -> Result<(), GameError> {
+        Err(GameError::NoChoice)
     }
 }
 
 impl fmt::Display for ColorCard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} {:?}\n", self.value, self.color)
+        writeln!(f, "{} {:?}", self.value, self.color)
     }
 }
 
@@ -249,14 +335,14 @@ impl Card for WhiteFlagCard {
         false
     }
 
-    fn set_card_effect(&mut self, _player_choice: CardEffect) {
-        panic!("Cannot set card type on WhiteFlagCard");
+    fn set_card_effect(&mut self, _player_choice: CardEffect) -> Result<(), GameError> {
+        Err(GameError::NoChoice)
     }
 }
 
 impl fmt::Display for WhiteFlagCard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "WhiteFlag\n")
+        writeln!(f, "WhiteFlag")
     }
 }
 
@@ -285,14 +371,14 @@ impl Card for PirateCard {
         false
     }
 
-    fn set_card_effect(&mut self, _player_choice: CardEffect) {
-        panic!("Cannot set card type on PirateCard");
+    fn set_card_effect(&mut self, _player_choice: CardEffect) -> Result<(), GameError> {
+        Err(GameError::NoChoice)
     }
 }
 
 impl fmt::Display for PirateCard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Pirate\n")
+        writeln!(f, "Pirate")
     }
 }
 
@@ -324,14 +410,14 @@ impl Card for MermaidCard {
         false
     }
 
-    fn set_card_effect(&mut self, _player_choice: CardEffect) {
-        panic!("Cannot set card type on MermaidCard");
+    fn set_card_effect(&mut self, _player_choice: CardEffect) -> Result<(), GameError> {
+        Err(GameError::NoChoice)
     }
 }
 
 impl fmt::Display for MermaidCard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Mermaid\n")
+        writeln!(f, "Mermaid")
     }
 }
 
@@ -364,14 +450,15 @@ impl Card for MarySueCard {
         false
     }
 
-    fn set_card_effect(&mut self, player_choice: CardEffect) {
+    fn set_card_effect(&mut self, player_choice: CardEffect) -> Result<(), GameError> {
         self.choice = Some(player_choice);
+        Ok(())
     }
 }
 
 impl fmt::Display for MarySueCard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "MarySue (choices {:?})\n", self.choice)
+        writeln!(f, "MarySue (choices {:?})", self.choice)
     }
 }
 
@@ -400,126 +487,474 @@ impl Card for SkullKingCard {
         false
     }
 
-    fn set_card_effect(&mut self, _player_choice: CardEffect) {
-        panic!("Cannot set card type on SkullKingCard");
+    fn set_card_effect(&mut self, _player_choice: CardEffect) -> Result<(), GameError> {
+        Err(GameError::NoChoice)
     }
 }
 
 impl fmt::Display for SkullKingCard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "SkullKing\n")
+        writeln!(f, "SkullKing")
     }
 }
 
 impl Special for SkullKingCard {}
 impl SkullKing for SkullKingCard {}
 
+impl Card for KrakenCard {
+    fn card_type(&self) -> CardTypeName {
+        CardTypeName::Kraken
+    }
+
+    fn card_color(&self) -> CardColor {
+        CardColor::DarkBlue
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn card_value(&self) -> Option<i32> {
+        None
+    }
+
+    fn is_card_special(&self) -> bool {
+        true
+    }
+
+    fn is_card_atout(&self) -> bool {
+        false
+    }
+
+    fn set_card_effect(&mut self, _player_choice: CardEffect) -> Result<(), GameError> {
+        Err(GameError::NoChoice)
+    }
+}
+
+impl fmt::Display for KrakenCard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Kraken")
+    }
+}
+
+impl Card for WhiteWhaleCard {
+    fn card_type(&self) -> CardTypeName {
+        CardTypeName::WhiteWhale
+    }
+
+    fn card_color(&self) -> CardColor {
+        CardColor::White
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn card_value(&self) -> Option<i32> {
+        None
+    }
+
+    fn is_card_special(&self) -> bool {
+        true
+    }
+
+    fn is_card_atout(&self) -> bool {
+        false
+    }
+
+    fn set_card_effect(&mut self, _player_choice: CardEffect) -> Result<(), GameError> {
+        Err(GameError::NoChoice)
+    }
+}
+
+impl fmt::Display for WhiteWhaleCard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "WhiteWhale")
+    }
+}
+
+impl Card for LootCard {
+    fn card_type(&self) -> CardTypeName {
+        CardTypeName::Loot
+    }
+
+    fn card_color(&self) -> CardColor {
+        CardColor::Brown
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn card_value(&self) -> Option<i32> {
+        None
+    }
+
+    fn is_card_special(&self) -> bool {
+        true
+    }
+
+    fn is_card_atout(&self) -> bool {
+        false
+    }
+
+    fn set_card_effect(&mut self, _player_choice: CardEffect) -> Result<(), GameError> {
+        Err(GameError::NoChoice)
+    }
+}
+
+impl fmt::Display for LootCard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Loot")
+    }
+}
+
+/// Numbered cards need their `card_number`, and colored ones their
+/// `card_color` too.
 fn new_card(
     card_type: CardTypeName,
     card_number: Option<i32>,
     card_color: Option<CardColor>,
-) -> PlayCard {
-    match card_type {
+) -> Result<CardType, GameError> {
+    let card = match card_type {
         CardTypeName::Color => CardType::Color(ColorCard {
-            color: card_color.unwrap(),
-            value: card_number.unwrap(),
+            color: card_color.ok_or(GameError::InvalidCard)?,
+            value: card_number.ok_or(GameError::InvalidCard)?,
         }),
         CardTypeName::Skull => CardType::Skull(SkullCard {
-            value: card_number.unwrap(),
+            value: card_number.ok_or(GameError::InvalidCard)?,
         }),
         CardTypeName::MarySue => CardType::MarySue(MarySueCard { choice: None }),
         CardTypeName::Flag => CardType::Flag(WhiteFlagCard {}),
         CardTypeName::Mermaid => CardType::Mermaid(MermaidCard {}),
         CardTypeName::Pirate => CardType::Pirate(PirateCard {}),
         CardTypeName::SkullKing => CardType::SkullKing(SkullKingCard {}),
-    }
+        CardTypeName::Kraken => CardType::Kraken(KrakenCard {}),
+        CardTypeName::WhiteWhale => CardType::WhiteWhale(WhiteWhaleCard {}),
+        CardTypeName::Loot => CardType::Loot(LootCard {}),
+    };
+    Ok(card)
 }
 
-pub fn create_deck(nb_per_color: i32) -> Deck {
-    const WHITE_FLAG_NB: i32 = 5;
-    const PIRATE_NB: i32 = 5;
-    const MERMAID_NB: i32 = 2;
+#[cfg(test)]
+#[test]
+fn test_card_errors() {
+    let mut pirate = PirateCard {};
+    assert_eq!(
+        pirate.set_card_effect(CardEffect::Flag),
+        Err(GameError::NoChoice)
+    );
+    assert_eq!(
+        new_card(CardTypeName::Color, Some(5), None).err(),
+        Some(GameError::InvalidCard)
+    );
+    assert_eq!(
+        new_card(CardTypeName::Skull, None, None).err(),
+        Some(GameError::InvalidCard)
+    );
+}
 
-    let mut result = Deck { cards: vec![] };
+/// Highest number a card may carry in a configured deck.
+pub const MAX_CARD_VALUE: i32 = 20;
+/// Most copies of one special card a configured deck may hold.
+pub const MAX_SPECIAL_COUNT: i32 = 10;
+
+/// Which cards make up a deck. Every suit is numbered from `min_value` to
+/// `max_value`; a Black suit is made of Skull trumps.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeckConfig {
+    pub colors: Vec<CardColor>,
+    pub min_value: i32,
+    pub max_value: i32,
+    pub flags: i32,
+    pub pirates: i32,
+    pub mermaids: i32,
+    pub skull_kings: i32,
+    pub mary_sues: i32,
+}
 
-    for color in vec![CardColor::Red, CardColor::Blue, CardColor::Green] {
-        for val in 1..=nb_per_color {
-            result
-                .cards
-                .push(new_card(CardTypeName::Color, Some(val), Some(color)));
-        }
+impl Default for DeckConfig {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    for val in 1..=nb_per_color {
-        result
-            .cards
-            .push(new_card(CardTypeName::Skull, Some(val), None));
+impl DeckConfig {
+    pub fn new() -> Self {
+        DeckConfig {
+            colors: vec![
+                CardColor::Red,
+                CardColor::Blue,
+                CardColor::Green,
+                CardColor::Black,
+            ],
+            min_value: 1,
+            max_value: 14,
+            flags: 5,
+            pirates: 5,
+            mermaids: 2,
+            skull_kings: 1,
+            mary_sues: 1,
+        }
     }
 
-    for _flag in 0..WHITE_FLAG_NB {
-        result.cards.push(new_card(CardTypeName::Flag, None, None));
+    pub fn validate(&self) -> Result<(), GameError> {
+        if self.colors.is_empty() {
+            return Err(GameError::InvalidSettings {
+                reason: "the deck needs at least one color",
+            });
+        }
+        if (1..self.colors.len()).any(|index| self.colors[..index].contains(&self.colors[index])) {
+            return Err(GameError::InvalidSettings {
+                reason: "each color can only be in the deck once",
+            });
+        }
+        if self.min_value < 1 || self.max_value < self.min_value {
+            return Err(GameError::InvalidSettings {
+                reason: "card values must be a range starting at 1 or more",
+            });
+        }
+        if self.max_value > MAX_CARD_VALUE {
+            return Err(GameError::SettingTooHigh {
+                setting: "card values",
+                max: MAX_CARD_VALUE,
+            });
+        }
+        let counts = [
+            self.flags,
+            self.pirates,
+            self.mermaids,
+            self.skull_kings,
+            self.mary_sues,
+        ];
+        if counts.iter().any(|&count| count < 0) {
+            return Err(GameError::InvalidSettings {
+                reason: "card counts cannot be negative",
+            });
+        }
+        if counts.iter().any(|&count| count > MAX_SPECIAL_COUNT) {
+            return Err(GameError::SettingTooHigh {
+                setting: "copies of a special card",
+                max: MAX_SPECIAL_COUNT,
+            });
+        }
+        Ok(())
+    }
+
+    /// Number of cards in the deck this config creates, `None` when the
+    /// counts do not make sense.
+    pub fn card_count(&self) -> Option<usize> {
+        let per_color = self.max_value.checked_sub(self.min_value)?.checked_add(1)?;
+        let mut count = usize::try_from(per_color)
+            .ok()?
+            .checked_mul(self.colors.len())?;
+        for special in [
+            self.flags,
+            self.pirates,
+            self.mermaids,
+            self.skull_kings,
+            self.mary_sues,
+        ] {
+            count = count.checked_add(usize::try_from(special).ok()?)?;
+        }
+        Some(count)
     }
 
-    for _pirate in 0..PIRATE_NB {
-        result
-            .cards
-            .push(new_card(CardTypeName::Pirate, None, None));
-    }
+    pub fn create_deck(&self) -> Result<Deck, GameError> {
+        self.validate()?;
+        let mut result = Deck { cards: vec![] };
 
-    for _mermaid in 0..MERMAID_NB {
-        result
-            .cards
-            .push(new_card(CardTypeName::Mermaid, None, None));
-    }
+        for &color in &self.colors {
+            for val in self.min_value..=self.max_value {
+                let card = match color {
+                    CardColor::Black => new_card(CardTypeName::Skull, Some(val), None),
+                    color => new_card(CardTypeName::Color, Some(val), Some(color)),
+                };
+                result.push_new(card?);
+            }
+        }
 
-    result
-        .cards
-        .push(new_card(CardTypeName::SkullKing, None, None));
-    result
-        .cards
-        .push(new_card(CardTypeName::MarySue, None, None));
+        for (card_type, count) in [
+            (CardTypeName::Flag, self.flags),
+            (CardTypeName::Pirate, self.pirates),
+            (CardTypeName::Mermaid, self.mermaids),
+            (CardTypeName::SkullKing, self.skull_kings),
+            (CardTypeName::MarySue, self.mary_sues),
+        ] {
+            for _card in 0..count {
+                result.push_new(new_card(card_type, None, None)?);
+            }
+        }
 
-    return result;
+        Ok(result)
+    }
 }
 
-pub fn create_default_deck() -> Deck {
-    create_deck(13)
+/// The standard deck, 70 cards: every suit runs from 1 to 14 so the 14s
+/// that scoring pays a capture bonus for are in play.
+pub fn create_default_deck() -> Result<Deck, GameError> {
+    DeckConfig::new().create_deck()
 }
 
-pub fn beats(first: &PlayCard, second: &PlayCard) -> bool {
+/// Shuffles in the expansion: one Kraken, one White Whale and two Loot.
+pub fn add_expansion_cards(deck: &mut Deck) -> Result<(), GameError> {
+    const LOOT_NB: i32 = 2;
+
+    deck.push_new(new_card(CardTypeName::Kraken, None, None)?);
+    deck.push_new(new_card(CardTypeName::WhiteWhale, None, None)?);
+    for _loot in 0..LOOT_NB {
+        deck.push_new(new_card(CardTypeName::Loot, None, None)?);
+    }
+    Ok(())
+}
+
+pub fn beats(first: &CardType, second: &CardType) -> bool {
     match (first, second) {
-        (_, CardType::Flag(_)) => return true,
-        (CardType::Pirate(_), CardType::SkullKing(_)) => return false,
-        (CardType::Pirate(_), _) => return true,
-        (CardType::Mermaid(_), CardType::Pirate(_)) => return false,
-        (CardType::Mermaid(_), _) => return true, // todo mermaid beats pirate if there is a skullking
-        (CardType::SkullKing(_), _) => return true,
-        (CardType::Skull(first), CardType::Skull(second)) => {
-            return first.card_value().unwrap() > second.card_value().unwrap();
-        }
-        (CardType::Skull(_), _) => return true,
+        (_, CardType::Flag(_)) => true,
+        (CardType::Pirate(_), CardType::SkullKing(_)) => false,
+        (CardType::Pirate(_), _) => true,
+        (CardType::Mermaid(_), CardType::Pirate(_)) => false,
+        (CardType::Mermaid(_), _) => true, // see trick_winner when a skullking is also played
+        (CardType::SkullKing(_), CardType::Mermaid(_)) => false,
+        (CardType::SkullKing(_), _) => true,
+        (CardType::Skull(first), CardType::Skull(second)) => first.value > second.value,
+        (CardType::Skull(_), CardType::Color(_)) => true,
         (CardType::Color(first), CardType::Color(second)) => {
             if first.card_color() == second.card_color() {
-                return first.card_value().unwrap() > second.card_value().unwrap();
+                return first.value > second.value;
             }
-            return true;
+            true
+        }
+        (_, _) => false,
+    }
+}
+
+/// How a complete trick ends, by index in play order.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TrickOutcome {
+    Won(usize),
+    /// Nobody takes the cards; `leader` opens the next trick.
+    Destroyed {
+        leader: usize,
+    },
+}
+
+impl TrickOutcome {
+    /// Index of the card whose player leads the next trick.
+    pub fn leader(&self) -> usize {
+        match *self {
+            TrickOutcome::Won(winner) => winner,
+            TrickOutcome::Destroyed { leader } => leader,
         }
-        (_, _) => return false,
+    }
+}
+
+/// Index of the card taking a trick given in play order, `None` when the
+/// trick is empty or destroyed.
+pub fn trick_winner(trick: &[CardType]) -> Option<usize> {
+    match trick_outcome(trick)? {
+        TrickOutcome::Won(winner) => Some(winner),
+        TrickOutcome::Destroyed { .. } => None,
+    }
+}
+
+/// Resolves a trick given in play order, expansion cards included.
+///
+/// The Kraken destroys the trick and whoever would have won it leads next.
+/// The White Whale strips the specials of their power: the highest number
+/// wins whatever its suit, and a trick without numbers is destroyed with
+/// the Whale's player leading. When both are down, the later one counts.
+pub fn trick_outcome(trick: &[CardType]) -> Option<TrickOutcome> {
+    let kraken = trick
+        .iter()
+        .position(|card| card.card_type_name() == CardTypeName::Kraken);
+    let white_whale = trick
+        .iter()
+        .position(|card| card.card_type_name() == CardTypeName::WhiteWhale);
+
+    match (kraken, white_whale) {
+        (Some(kraken), Some(white_whale)) if white_whale > kraken => {
+            Some(white_whale_outcome(trick, white_whale))
+        }
+        (None, Some(white_whale)) => Some(white_whale_outcome(trick, white_whale)),
+        (Some(_), _) => Some(TrickOutcome::Destroyed {
+            leader: base_winner(trick)?,
+        }),
+        (None, None) => base_winner(trick).map(TrickOutcome::Won),
+    }
+}
+
+fn white_whale_outcome(trick: &[CardType], white_whale: usize) -> TrickOutcome {
+    let highest = trick
+        .iter()
+        .enumerate()
+        .filter_map(|(index, card)| Some((index, card.value()?)))
+        // the first of equal numbers wins
+        .max_by_key(|&(index, value)| (value, std::cmp::Reverse(index)));
+    match highest {
+        Some((index, _)) => TrickOutcome::Won(index),
+        None => TrickOutcome::Destroyed {
+            leader: white_whale,
+        },
+    }
+}
+
+/// Index of the winning card under the base rules, the expansion cards
+/// counting as escapes.
+///
+/// Unlike `beats`, this looks at the whole trick: a Mermaid takes the Skull
+/// King even when a Pirate is also down, the first of several Pirates or
+/// Mermaids wins, and a trick of only Flags goes to the first one.
+fn base_winner(trick: &[CardType]) -> Option<usize> {
+    let first_of = |name| trick.iter().position(|card| card.card_type_name() == name);
+    let skull_king = first_of(CardTypeName::SkullKing);
+    let mermaid = first_of(CardTypeName::Mermaid);
+    let pirate = first_of(CardTypeName::Pirate);
+
+    match (skull_king, mermaid, pirate) {
+        (Some(_), Some(mermaid), _) => return Some(mermaid),
+        (Some(skull_king), None, _) => return Some(skull_king),
+        (None, _, Some(pirate)) => return Some(pirate),
+        (None, Some(mermaid), None) => return Some(mermaid),
+        (None, None, None) => {}
+    }
+
+    // Only numbered cards and flags left: black trumps the lead suit.
+    let trump = if trick
+        .iter()
+        .any(|card| card.suit() == Some(CardColor::Black))
+    {
+        Some(CardColor::Black)
+    } else {
+        trick.iter().find_map(|card| card.suit())
+    };
+    let best = trick
+        .iter()
+        .enumerate()
+        .filter(|(_, card)| trump.is_some() && card.suit() == trump)
+        .max_by_key(|(_, card)| card.value())
+        .map(|(index, _)| index);
+
+    match best {
+        Some(index) => Some(index),
+        None if trick.is_empty() => None,
+        None => Some(0),
     }
 }
 
 #[cfg(test)]
 #[test]
 fn test_beats() {
-    let pirate = new_card(CardTypeName::Pirate, None, None);
-    let skull_king = new_card(CardTypeName::SkullKing, None, None);
-    let flag = new_card(CardTypeName::Flag, None, None);
-    let mermaid = new_card(CardTypeName::Mermaid, None, None);
-    let skull = new_card(CardTypeName::Skull, Some(5), None);
-    let skull_2 = new_card(CardTypeName::Skull, Some(3), None);
-    let color_red_5 = new_card(CardTypeName::Color, Some(5), Some(CardColor::Red));
-    let color_red_3 = new_card(CardTypeName::Color, Some(3), Some(CardColor::Red));
-    let color_blue_5 = new_card(CardTypeName::Color, Some(5), Some(CardColor::Blue));
+    let pirate = new_card(CardTypeName::Pirate, None, None).unwrap();
+    let skull_king = new_card(CardTypeName::SkullKing, None, None).unwrap();
+    let flag = new_card(CardTypeName::Flag, None, None).unwrap();
+    let mermaid = new_card(CardTypeName::Mermaid, None, None).unwrap();
+    let skull = new_card(CardTypeName::Skull, Some(5), None).unwrap();
+    let skull_2 = new_card(CardTypeName::Skull, Some(3), None).unwrap();
+    let color_red_5 = new_card(CardTypeName::Color, Some(5), Some(CardColor::Red)).unwrap();
+    let color_red_3 = new_card(CardTypeName::Color, Some(3), Some(CardColor::Red)).unwrap();
+    let color_blue_5 = new_card(CardTypeName::Color, Some(5), Some(CardColor::Blue)).unwrap();
 
     assert!(beats(&pirate, &flag));
     assert!(!beats(&pirate, &skull_king));
@@ -533,4 +968,203 @@ fn test_beats() {
     assert!(beats(&color_red_5, &color_red_3));
     assert!(beats(&color_red_5, &color_blue_5));
     assert!(!beats(&color_red_3, &color_red_5));
+    assert!(!beats(&skull_king, &mermaid));
+    assert!(!beats(&skull, &pirate));
+    assert!(!beats(&skull, &skull_king));
+    assert!(!beats(&color_red_5, &skull_2));
+}
+
+#[cfg(test)]
+#[test]
+fn test_trick_winner() {
+    let pirate = new_card(CardTypeName::Pirate, None, None).unwrap();
+    let skull_king = new_card(CardTypeName::SkullKing, None, None).unwrap();
+    let flag = new_card(CardTypeName::Flag, None, None).unwrap();
+    let mermaid = new_card(CardTypeName::Mermaid, None, None).unwrap();
+    let skull = new_card(CardTypeName::Skull, Some(2), None).unwrap();
+    let color_red_5 = new_card(CardTypeName::Color, Some(5), Some(CardColor::Red)).unwrap();
+    let color_red_9 = new_card(CardTypeName::Color, Some(9), Some(CardColor::Red)).unwrap();
+    let color_blue_13 = new_card(CardTypeName::Color, Some(13), Some(CardColor::Blue)).unwrap();
+
+    assert_eq!(trick_winner(&[]), None);
+    assert_eq!(trick_winner(&[pirate, skull_king, mermaid]), Some(2));
+    assert_eq!(trick_winner(&[mermaid, pirate, skull_king]), Some(0));
+    assert_eq!(trick_winner(&[skull_king, pirate, color_red_9]), Some(0));
+    assert_eq!(trick_winner(&[color_red_5, pirate, pirate]), Some(1));
+    assert_eq!(trick_winner(&[mermaid, color_red_5, mermaid]), Some(0));
+    assert_eq!(trick_winner(&[mermaid, pirate, mermaid]), Some(1));
+    assert_eq!(trick_winner(&[flag, flag, flag]), Some(0));
+    assert_eq!(
+        trick_winner(&[flag, color_red_5, color_blue_13, color_red_9]),
+        Some(3)
+    );
+    assert_eq!(trick_winner(&[color_red_9, skull, color_blue_13]), Some(1));
+}
+
+#[cfg(test)]
+#[test]
+fn test_expansion_tricks() {
+    let pirate = new_card(CardTypeName::Pirate, None, None).unwrap();
+    let skull_king = new_card(CardTypeName::SkullKing, None, None).unwrap();
+    let flag = new_card(CardTypeName::Flag, None, None).unwrap();
+    let kraken = new_card(CardTypeName::Kraken, None, None).unwrap();
+    let white_whale = new_card(CardTypeName::WhiteWhale, None, None).unwrap();
+    let loot = new_card(CardTypeName::Loot, None, None).unwrap();
+    let skull = new_card(CardTypeName::Skull, Some(2), None).unwrap();
+    let color_red_9 = new_card(CardTypeName::Color, Some(9), Some(CardColor::Red)).unwrap();
+    let color_blue_9 = new_card(CardTypeName::Color, Some(9), Some(CardColor::Blue)).unwrap();
+
+    assert_eq!(
+        trick_outcome(&[color_red_9, kraken, pirate]),
+        Some(TrickOutcome::Destroyed { leader: 2 })
+    );
+    assert_eq!(trick_winner(&[color_red_9, kraken, pirate]), None);
+    assert_eq!(
+        trick_outcome(&[skull_king, skull, white_whale, color_red_9]),
+        Some(TrickOutcome::Won(3))
+    );
+    assert_eq!(
+        trick_outcome(&[color_blue_9, white_whale, color_red_9]),
+        Some(TrickOutcome::Won(0))
+    );
+    assert_eq!(
+        trick_outcome(&[pirate, white_whale, flag]),
+        Some(TrickOutcome::Destroyed { leader: 1 })
+    );
+    assert_eq!(
+        trick_outcome(&[white_whale, kraken, color_red_9]),
+        Some(TrickOutcome::Destroyed { leader: 2 })
+    );
+    assert_eq!(
+        trick_outcome(&[kraken, white_whale, color_red_9]),
+        Some(TrickOutcome::Won(2))
+    );
+    assert_eq!(trick_winner(&[loot, color_red_9, loot]), Some(1));
+    assert_eq!(trick_winner(&[loot, flag]), Some(0));
+}
+
+#[cfg(test)]
+#[test]
+fn test_deck_config() {
+    let config = DeckConfig::new();
+    assert_eq!(config.card_count(), Some(70));
+    assert_eq!(config.create_deck().unwrap().cards.len(), 70);
+
+    let small = DeckConfig {
+        colors: vec![CardColor::Pink, CardColor::Black],
+        min_value: 3,
+        max_value: 8,
+        pirates: 2,
+        mary_sues: 0,
+        ..DeckConfig::new()
+    };
+    let deck = small.create_deck().unwrap();
+    assert_eq!(Some(deck.cards.len()), small.card_count());
+    assert_eq!(deck.cards.len(), 12 + 5 + 2 + 2 + 1);
+    assert!(deck.cards.iter().all(|card| !card.card_type.is_mary_sue()));
+    assert_eq!(deck.cards[0].card_type.suit(), Some(CardColor::Pink));
+    assert_eq!(
+        deck.cards[6].card_type.card_type_name(),
+        CardTypeName::Skull
+    );
+
+    // every physical card gets its own id, expansion cards included
+    let mut deck = config.create_deck().unwrap();
+    add_expansion_cards(&mut deck).unwrap();
+    let ids: Vec<CardId> = deck.cards.iter().map(|card| card.id).collect();
+    assert_eq!(ids, (0..74).collect::<Vec<CardId>>());
+
+    for invalid in [
+        DeckConfig {
+            colors: vec![],
+            ..DeckConfig::new()
+        },
+        DeckConfig {
+            colors: vec![CardColor::Red, CardColor::Red],
+            ..DeckConfig::new()
+        },
+        DeckConfig {
+            min_value: 9,
+            max_value: 2,
+            ..DeckConfig::new()
+        },
+        DeckConfig {
+            flags: -1,
+            ..DeckConfig::new()
+        },
+        DeckConfig {
+            max_value: i32::MAX,
+            ..DeckConfig::new()
+        },
+        DeckConfig {
+            pirates: 1_000_000,
+            ..DeckConfig::new()
+        },
+    ] {
+        assert!(invalid.validate().is_err());
+        assert!(invalid.create_deck().is_err());
+    }
+    assert_eq!(
+        DeckConfig {
+            max_value: MAX_CARD_VALUE + 1,
+            ..DeckConfig::new()
+        }
+        .validate(),
+        Err(GameError::SettingTooHigh {
+            setting: "card values",
+            max: MAX_CARD_VALUE
+        })
+    );
+    let overflowing = DeckConfig {
+        min_value: i32::MIN,
+        max_value: i32::MAX,
+        ..DeckConfig::new()
+    };
+    assert_eq!(overflowing.card_count(), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_card_json() {
+    use rocket::serde::json;
+
+    let color_red_5 = new_card(CardTypeName::Color, Some(5), Some(CardColor::Red)).unwrap();
+    let mut mary_sue = new_card(CardTypeName::MarySue, None, None).unwrap();
+    if let CardType::MarySue(card) = &mut mary_sue {
+        card.set_card_effect(CardEffect::Flag).unwrap();
+    }
+
+    assert_eq!(
+        json::to_string(&color_red_5).unwrap(),
+        r#"{"type":"Color","color":"Red","value":5}"#
+    );
+    assert_eq!(
+        json::to_string(&mary_sue).unwrap(),
+        r#"{"type":"MarySue","choice":"Flag"}"#
+    );
+    let parsed: CardType = json::from_str(r#"{"type":"Pirate"}"#).unwrap();
+    assert_eq!(parsed.card_type_name(), CardTypeName::Pirate);
+
+    let card = PlayCard {
+        id: 7,
+        card_type: color_red_5,
+    };
+    let json_card = r#"{"id":7,"type":"Color","color":"Red","value":5}"#;
+    assert_eq!(json::to_string(&card).unwrap(), json_card);
+    let parsed: PlayCard = json::from_str(json_card).unwrap();
+    assert_eq!(parsed.id, 7);
+    assert_eq!(parsed.card_type.value(), Some(5));
+}
+
+/// Numbers cards in order, for tests dealing hands by hand.
+#[cfg(test)]
+pub(crate) fn with_ids(cards: Vec<CardType>) -> Vec<PlayCard> {
+    cards
+        .into_iter()
+        .enumerate()
+        .map(|(id, card_type)| PlayCard {
+            id: id as CardId,
+            card_type,
+        })
+        .collect()
 }
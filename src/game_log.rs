@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bot::BotKind,
+    error::GameError,
+    game::{CardEffect, CardId, PlayCard},
+    lifecycle::Game,
+    lobby::TableSettings,
+    online_board,
+};
+
+/// One step of a game, in the order it happened. Replaying the log from an
+/// empty table rebuilds the exact same table and bots, hands and deck
+/// included. Session tokens are never logged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event")]
+pub enum GameEvent {
+    PlayerJoined {
+        player_id: i32,
+        name: String,
+        /// Set when a bot of this kind takes the seat.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bot: Option<BotKind>,
+    },
+    PlayerLeft {
+        player_id: i32,
+    },
+    /// Seed every shuffle of the game derives from.
+    Seeded {
+        seed: u64,
+    },
+    SeatsArranged {
+        order: Vec<i32>,
+    },
+    /// The whole shuffled deck, dealt from the end.
+    Dealt {
+        round: i32,
+        starting_seat: usize,
+        deck: Vec<PlayCard>,
+    },
+    BidPlaced {
+        player_id: i32,
+        bid: i32,
+    },
+    CardPlayed {
+        player_id: i32,
+        card_id: CardId,
+        choice: Option<CardEffect>,
+    },
+    /// Follows from the cards played, kept for review.
+    TrickWon {
+        player_id: i32,
+    },
+    /// Follows from the cards played, kept for review.
+    TrickDestroyed {
+        next_player_id: i32,
+    },
+    /// Follows from the cards played, kept for review.
+    RoundEnded {
+        round: i32,
+        points: Vec<(i32, i32)>,
+    },
+    GameEnded,
+}
+
+impl Game {
+    /// Rebuilds a game by replaying `log` on an empty table. Replaying only
+    /// the first steps of a log gives the game as it was at that point.
+    pub fn replay(settings: TableSettings, log: &[GameEvent]) -> Result<Game, GameError> {
+        let mut game = Game::with_settings(settings)?;
+        for event in log {
+            game.apply(event)?;
+        }
+        Ok(game)
+    }
+
+    /// Winner of the first trick closed after `step` log entries, whoever
+    /// played its last card. `None` while that trick is still open or when
+    /// it was destroyed.
+    pub fn trick_winner_since(&self, step: usize) -> Option<i32> {
+        self.log
+            .get(step..)?
+            .iter()
+            .find_map(|event| match event {
+                GameEvent::TrickWon { player_id } => Some(Some(*player_id)),
+                GameEvent::TrickDestroyed { .. } => Some(None),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    fn apply(&mut self, event: &GameEvent) -> Result<(), GameError> {
+        match event {
+            GameEvent::PlayerJoined { name, bot, .. } => {
+                self.seat_player(name.clone(), *bot)?;
+            }
+            GameEvent::PlayerLeft { player_id } => self.leave(*player_id)?,
+            GameEvent::Seeded { seed } => self.seed_table(*seed),
+            GameEvent::SeatsArranged { order } => {
+                // the live game drew the order from the table's first shuffle
+                self.table.next_rng();
+                self.arrange_seats(order.clone());
+            }
+            GameEvent::Dealt {
+                round,
+                starting_seat,
+                deck,
+            } => {
+                online_board::clear_table_after_game(&mut self.table);
+                self.starting_seat = *starting_seat;
+                self.deal(*round, deck.clone())?;
+            }
+            GameEvent::BidPlaced { player_id, bid } => self.bid(*player_id, *bid)?,
+            GameEvent::CardPlayed {
+                player_id,
+                card_id,
+                choice,
+            } => {
+                self.play_by_id(*player_id, *card_id, *choice)?;
+            }
+            GameEvent::TrickWon { .. }
+            | GameEvent::TrickDestroyed { .. }
+            | GameEvent::RoundEnded { .. } => {}
+            GameEvent::GameEnded => self.next_round()?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_replay_rebuilds_the_game() {
+    use crate::{bot::BotKind, lifecycle::GamePhase, lobby::RuleOptions};
+
+    let settings = TableSettings {
+        rules: RuleOptions {
+            rounds: 3,
+            expansion: true,
+            ..RuleOptions::new()
+        },
+        ..TableSettings::new()
+    };
+    let mut game = Game::with_settings(settings.clone()).unwrap();
+    game.seat_players(2);
+    game.add_bot(BotKind::Random).unwrap();
+    game.start().unwrap();
+    let mut checkpoint = None;
+    while game.phase != GamePhase::Finished {
+        match game.phase {
+            GamePhase::Bidding => {
+                for seat in game.table.seats.clone() {
+                    game.bid(seat.player.player_id, 1).unwrap();
+                }
+            }
+            GamePhase::Playing => {
+                game.play_out_round(CardEffect::Pirate);
+                if game.table.round == 2 {
+                    checkpoint = Some((game.log.len(), format!("{:?}", game.table)));
+                }
+            }
+            _ => game.next_round().unwrap(),
+        }
+    }
+
+    let replayed = Game::replay(settings.clone(), &game.log).unwrap();
+    assert_eq!(replayed.phase, GamePhase::Finished);
+    assert_eq!(format!("{:?}", replayed.table), format!("{:?}", game.table));
+    assert_eq!(format!("{:?}", replayed.bots), format!("{:?}", game.bots));
+    assert_eq!(replayed.host_id(), game.host_id());
+    assert_eq!(replayed.log.len(), game.log.len());
+
+    let (steps, table) = checkpoint.unwrap();
+    let partial = Game::replay(settings, &game.log[..steps]).unwrap();
+    assert_eq!(format!("{:?}", partial.table), table);
+}
@@ -0,0 +1,15 @@
+pub mod bot;
+pub mod error;
+pub mod events;
+pub mod game;
+pub mod game_log;
+pub mod lifecycle;
+pub mod lobby;
+pub mod online_board;
+pub mod registry;
+pub mod scoring;
+pub mod session;
+pub mod simulation;
+pub mod storage;
+pub mod user;
+pub mod view;
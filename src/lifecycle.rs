@@ -0,0 +1,475 @@
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bot::BotSeat,
+    error::GameError,
+    events::TableEvent,
+    game::{CardEffect, CardId, PlayCard},
+    game_log::GameEvent,
+    lobby::TableSettings,
+    online_board::{self, Table},
+    scoring,
+    session::PlayerSession,
+};
+
+pub const LAST_ROUND: i32 = 10;
+pub const MIN_PLAYERS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GamePhase {
+    Lobby,
+    Bidding,
+    Playing,
+    RoundScoring,
+    Finished,
+}
+
+/// A table and the phase it is in. Every action goes through here so it
+/// can be refused when it does not fit the current phase.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Game {
+    pub settings: TableSettings,
+    pub table: Table,
+    pub phase: GamePhase,
+    /// Seat leading the first trick of the round, moves one seat every round.
+    pub starting_seat: usize,
+    /// Seat leading the trick in progress.
+    pub leading_seat: usize,
+    /// Player ids are never reused, even after someone leaves the lobby.
+    pub next_player_id: i32,
+    pub sessions: Vec<PlayerSession>,
+    #[serde(default)]
+    pub bots: Vec<BotSeat>,
+    /// Everything that happened at the table, see `Game::replay`.
+    #[serde(default)]
+    pub log: Vec<GameEvent>,
+    /// Notifications waiting to be sent to the table's watchers.
+    #[serde(skip)]
+    pub events: Vec<TableEvent>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    pub const fn new() -> Self {
+        Game {
+            settings: TableSettings::new(),
+            table: Table::new(),
+            phase: GamePhase::Lobby,
+            starting_seat: 0,
+            leading_seat: 0,
+            next_player_id: 0,
+            sessions: Vec::new(),
+            bots: Vec::new(),
+            log: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Hands over the notifications raised since the last call.
+    pub fn take_events(&mut self) -> Vec<TableEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub(crate) fn expect_phase(&self, phase: GamePhase) -> Result<(), GameError> {
+        if self.phase != phase {
+            return Err(GameError::WrongPhase);
+        }
+        Ok(())
+    }
+
+    pub fn start(&mut self) -> Result<(), GameError> {
+        let seed = self.settings.rules.seed.unwrap_or_else(rand::random);
+        self.start_with_seed(seed)
+    }
+
+    /// Starts the game with every shuffle drawn from `seed`, so the same
+    /// seed and players always get the same cards.
+    pub fn start_with_seed(&mut self, seed: u64) -> Result<(), GameError> {
+        self.expect_phase(GamePhase::Lobby)?;
+        if self.table.seats.len() < MIN_PLAYERS {
+            return Err(GameError::NotEnoughPlayers);
+        }
+        let deck = self.settings.rules.deck()?;
+        self.seed_table(seed);
+        let mut order: Vec<i32> = self
+            .table
+            .seats
+            .iter()
+            .map(|seat| seat.player.player_id)
+            .collect();
+        order.shuffle(&mut self.table.next_rng());
+        self.arrange_seats(order);
+        self.table.deck = deck;
+        self.starting_seat = 0;
+        self.start_round(1)
+    }
+
+    pub(crate) fn seed_table(&mut self, seed: u64) {
+        self.table.seed = seed;
+        self.table.shuffles = 0;
+        self.log.push(GameEvent::Seeded { seed });
+    }
+
+    /// Seats the players in the given order of `player_id`s.
+    pub(crate) fn arrange_seats(&mut self, order: Vec<i32>) {
+        self.table.seats.sort_by_key(|seat| {
+            order
+                .iter()
+                .position(|&player_id| player_id == seat.player.player_id)
+        });
+        self.table.scoreboard = scoring::Scoreboard::new();
+        self.log.push(GameEvent::SeatsArranged { order });
+    }
+
+    /// Collects every card back and shuffles before dealing the round.
+    fn start_round(&mut self, round: i32) -> Result<(), GameError> {
+        online_board::clear_table_after_game(&mut self.table);
+        let deck = self.table.deck.cards.clone();
+        self.deal(round, deck)
+    }
+
+    /// Last round of this game, see `RuleOptions::last_round`.
+    pub fn last_round(&self) -> i32 {
+        self.settings.rules.last_round(self.table.seats.len())
+    }
+
+    /// Deals `round` cards per seat from the end of `deck`, which must hold
+    /// every card of the game.
+    pub(crate) fn deal(&mut self, round: i32, deck: Vec<PlayCard>) -> Result<(), GameError> {
+        if deck.len() < round as usize * self.table.seats.len() {
+            return Err(GameError::DeckExhausted);
+        }
+        self.log.push(GameEvent::Dealt {
+            round,
+            starting_seat: self.starting_seat,
+            deck: deck.clone(),
+        });
+        if round == 1 {
+            self.table.card_count = deck.len();
+        }
+        self.table.deck.cards = deck;
+        self.table.round = round;
+        for _ in 0..round {
+            for seat in self.table.seats.iter_mut() {
+                if let Some(card) = self.table.deck.cards.pop() {
+                    seat.hand.push(card);
+                }
+            }
+        }
+        self.leading_seat = self.starting_seat;
+        self.phase = GamePhase::Bidding;
+        self.events.push(TableEvent::RoundStarted { round });
+        debug_assert_eq!(self.check_cards(), Ok(()));
+        Ok(())
+    }
+
+    /// Confirms no card of the game was lost or duplicated while moving
+    /// between the deck, hands, river, plis and discard. Checked after every
+    /// move in debug builds, and by the registry after every action.
+    pub fn check_cards(&self) -> Result<(), GameError> {
+        let card_count = match self.phase {
+            GamePhase::Lobby => 0,
+            // snapshots saved before the count was kept
+            _ if self.table.card_count == 0 => self.settings.rules.deck()?.cards.len(),
+            _ => self.table.card_count,
+        };
+        online_board::check_cards(&self.table, card_count)
+    }
+
+    pub fn bid(&mut self, player_id: i32, bid: i32) -> Result<(), GameError> {
+        self.expect_phase(GamePhase::Bidding)?;
+        online_board::place_bid(&mut self.table, player_id, bid)?;
+        self.log.push(GameEvent::BidPlaced { player_id, bid });
+        if let Some(bids) = online_board::revealed_bids(&self.table) {
+            self.phase = GamePhase::Playing;
+            self.events.push(TableEvent::BidsRevealed { bids });
+        }
+        Ok(())
+    }
+
+    /// Plays the card with id `card_id` from the hand of `player_id`, see
+    /// `play`.
+    pub fn play_by_id(
+        &mut self,
+        player_id: i32,
+        card_id: CardId,
+        choice: Option<CardEffect>,
+    ) -> Result<Option<i32>, GameError> {
+        let seat = self.seat_of(player_id).ok_or(GameError::UnknownPlayer)?;
+        let card_index = self.table.seats[seat]
+            .hand
+            .iter()
+            .position(|card| card.id == card_id)
+            .ok_or(GameError::CardNotInHand)?;
+        self.play(player_id, card_index, choice)
+    }
+
+    /// Seat expected to play next in the trick in progress.
+    pub fn current_seat(&self) -> usize {
+        (self.leading_seat + self.table.river.len()) % self.table.seats.len()
+    }
+
+    /// Plays a card for `player_id`, resolving the trick once every seat has
+    /// played and scoring the round once every hand is empty. Returns the
+    /// trick winner when this card closed a trick.
+    pub fn play(
+        &mut self,
+        player_id: i32,
+        card_index: usize,
+        choice: Option<CardEffect>,
+    ) -> Result<Option<i32>, GameError> {
+        self.expect_phase(GamePhase::Playing)?;
+        let current_seat = self.current_seat();
+        let seat = &mut self.table.seats[current_seat];
+        if seat.player.player_id != player_id {
+            if self.seat_of(player_id).is_none() {
+                return Err(GameError::UnknownPlayer);
+            }
+            return Err(GameError::NotYourTurn);
+        }
+        online_board::play_card(&mut self.table.river, seat, card_index, choice)?;
+        if let Some(played_card) = self.table.river.last() {
+            self.log.push(GameEvent::CardPlayed {
+                player_id,
+                card_id: played_card.card().id,
+                choice,
+            });
+            self.events.push(TableEvent::CardPlayed {
+                player_id,
+                card: *played_card.card(),
+            });
+        }
+
+        if self.table.river.len() < self.table.seats.len() {
+            debug_assert_eq!(self.check_cards(), Ok(()));
+            return Ok(None);
+        }
+        let leader = online_board::next_leader(&self.table.river);
+        let highest_value = self.settings.rules.highest_value();
+        let winner = online_board::resolve_trick(&mut self.table, highest_value)?;
+        match (winner, leader) {
+            (Some(winner), _) => {
+                self.log.push(GameEvent::TrickWon { player_id: winner });
+                self.events.push(TableEvent::TrickWon { player_id: winner });
+            }
+            (None, Some(leader)) => {
+                self.log.push(GameEvent::TrickDestroyed {
+                    next_player_id: leader,
+                });
+                self.events.push(TableEvent::TrickDestroyed {
+                    next_player_id: leader,
+                });
+            }
+            (None, None) => {}
+        }
+        if let Some(leader_seat) = leader.and_then(|leader| self.seat_of(leader)) {
+            self.leading_seat = leader_seat;
+        }
+        if self.table.seats.iter().all(|seat| seat.hand.is_empty()) {
+            let points = scoring::score_round(&mut self.table);
+            self.phase = GamePhase::RoundScoring;
+            self.log.push(GameEvent::RoundEnded {
+                round: self.table.round,
+                points: points.clone(),
+            });
+            self.events.push(TableEvent::RoundScored {
+                round: self.table.round,
+                points,
+            });
+        }
+        debug_assert_eq!(self.check_cards(), Ok(()));
+        Ok(winner)
+    }
+
+    /// Leaves the scores of the round on display until someone moves on to
+    /// the next round, or ends the game after the last one.
+    pub fn next_round(&mut self) -> Result<(), GameError> {
+        self.expect_phase(GamePhase::RoundScoring)?;
+        if self.table.round >= self.last_round() {
+            self.phase = GamePhase::Finished;
+            self.log.push(GameEvent::GameEnded);
+            self.events.push(TableEvent::GameOver {
+                scoreboard: self.table.scoreboard.clone(),
+            });
+            return Ok(());
+        }
+        self.starting_seat = (self.starting_seat + 1) % self.table.seats.len();
+        self.start_round(self.table.round + 1)
+    }
+
+    pub fn seat_of(&self, player_id: i32) -> Option<usize> {
+        self.table
+            .seats
+            .iter()
+            .position(|seat| seat.player.player_id == player_id)
+    }
+}
+
+#[cfg(test)]
+const TEST_NAMES: [&str; 8] = [
+    "anne", "bart", "carl", "dave", "emma", "fred", "gina", "hugo",
+];
+
+#[cfg(test)]
+impl Game {
+    /// Seats `count` human players in the lobby, numbered from 1.
+    pub(crate) fn seat_players(&mut self, count: usize) {
+        for name in &TEST_NAMES[..count] {
+            self.add_player(name.to_string()).unwrap();
+        }
+    }
+
+    /// Plays the first legal card of every human until the tricks of the
+    /// round are over, letting the bots answer in between.
+    pub(crate) fn play_out_round(&mut self, choice: CardEffect) {
+        while self.phase == GamePhase::Playing {
+            self.run_bots(&mut rand::rng()).unwrap();
+            if self.phase != GamePhase::Playing {
+                break;
+            }
+            let seat = &self.table.seats[self.current_seat()];
+            let player_id = seat.player.player_id;
+            let card_index = online_board::legal_plays(&self.table.river, &seat.hand)[0];
+            self.play(player_id, card_index, Some(choice)).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_game_lifecycle() {
+    let mut game = Game::new();
+    assert_eq!(game.start(), Err(GameError::NotEnoughPlayers));
+    game.seat_players(3);
+    assert_eq!(game.bid(1, 0), Err(GameError::WrongPhase));
+    game.start().unwrap();
+    assert_eq!(
+        game.table.card_count,
+        game.settings.rules.deck().unwrap().cards.len()
+    );
+    assert_eq!(
+        game.add_player("late".to_string()),
+        Err(GameError::WrongPhase)
+    );
+
+    for round in 1..=LAST_ROUND {
+        assert_eq!(game.phase, GamePhase::Bidding);
+        assert_eq!(game.table.round, round);
+        assert!(game
+            .table
+            .seats
+            .iter()
+            .all(|seat| seat.hand.len() == round as usize));
+        for player_id in 1..=3 {
+            game.bid(player_id, 1).unwrap();
+        }
+        assert_eq!(game.phase, GamePhase::Playing);
+        assert_eq!(game.current_seat(), (round as usize - 1) % 3);
+
+        let seat = &game.table.seats[game.current_seat()];
+        let player_id = seat.player.player_id;
+        let card_index = online_board::legal_plays(&game.table.river, &seat.hand)[0];
+        let card_id = seat.hand[card_index].id;
+        let other_id = player_id % 3 + 1;
+        assert_eq!(game.play(other_id, 0, None), Err(GameError::NotYourTurn));
+        assert_eq!(
+            game.play_by_id(player_id, 999, None),
+            Err(GameError::CardNotInHand)
+        );
+        game.play_by_id(player_id, card_id, Some(CardEffect::Flag))
+            .unwrap();
+        game.play_out_round(CardEffect::Flag);
+        assert_eq!(game.phase, GamePhase::RoundScoring);
+        let tricks: i32 = game.table.seats.iter().map(|seat| seat.tricks_won).sum();
+        assert_eq!(tricks, round);
+        game.next_round().unwrap();
+    }
+    assert_eq!(game.phase, GamePhase::Finished);
+    assert_eq!(game.table.scoreboard.players[0].rounds.len(), 10);
+}
+
+#[cfg(test)]
+#[test]
+fn test_same_seed_same_cards() {
+    use crate::lobby::RuleOptions;
+
+    let seeded_game = |seed| {
+        let mut game = Game::with_settings(TableSettings {
+            rules: RuleOptions {
+                seed: Some(seed),
+                ..RuleOptions::new()
+            },
+            ..TableSettings::new()
+        })
+        .unwrap();
+        assert!(game.summary("seeded").seeded);
+        game.seat_players(4);
+        game.start().unwrap();
+        for player_id in 1..=4 {
+            game.bid(player_id, 0).unwrap();
+        }
+        game.play_out_round(CardEffect::Pirate);
+        game.next_round().unwrap();
+        format!("{}", game.table)
+    };
+
+    assert_eq!(seeded_game(42), seeded_game(42));
+    assert_ne!(seeded_game(42), seeded_game(43));
+}
+
+#[cfg(test)]
+#[test]
+fn test_eight_seats() {
+    use crate::{bot::BotKind, lobby::RuleOptions};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let eight_bots = |rules: RuleOptions| {
+        let mut game = Game::with_settings(TableSettings {
+            max_seats: 8,
+            rules,
+            ..TableSettings::new()
+        })
+        .unwrap();
+        for _ in 0..8 {
+            game.add_bot(BotKind::Random).unwrap();
+        }
+        game
+    };
+    let rounds_played = |mut game: Game| {
+        let mut rng = StdRng::seed_from_u64(8);
+        game.start_with_seed(8).unwrap();
+        while game.phase != GamePhase::Finished {
+            game.run_bots(&mut rng).unwrap();
+            game.next_round().unwrap();
+        }
+        game.table.scoreboard.players[0].rounds.len()
+    };
+
+    // 70 cards deal 8 rounds to 8 seats, the Yellow suit brings it to 84
+    let scaled = RuleOptions {
+        scale_rounds: true,
+        ..RuleOptions::new()
+    };
+    assert_eq!(eight_bots(scaled.clone()).last_round(), 8);
+    assert_eq!(rounds_played(eight_bots(scaled)), 8);
+    let fourth_suit = RuleOptions {
+        fourth_suit: true,
+        ..RuleOptions::new()
+    };
+    assert_eq!(rounds_played(eight_bots(fourth_suit)), 10);
+
+    let mut game = eight_bots(RuleOptions {
+        scale_rounds: true,
+        ..RuleOptions::new()
+    });
+    let short_deck = game.settings.rules.deck().unwrap().cards[..20].to_vec();
+    let logged = game.log.len();
+    assert_eq!(game.deal(3, short_deck), Err(GameError::DeckExhausted));
+    assert_eq!(game.log.len(), logged);
+}
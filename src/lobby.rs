@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bot::{BotKind, BotSeat},
+    error::GameError,
+    events::TableEvent,
+    game::{self, CardColor, Deck, DeckConfig},
+    game_log::GameEvent,
+    lifecycle::{Game, GamePhase, LAST_ROUND, MIN_PLAYERS},
+    online_board,
+    user::Player,
+};
+
+pub const DEFAULT_MAX_SEATS: usize = 6;
+/// Eight seats need the fourth suit or fewer rounds, see `RuleOptions`.
+pub const MAX_SEATS: usize = 8;
+
+/// Rule variations chosen when the table is created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleOptions {
+    /// Number of rounds, the last one dealing that many cards.
+    pub rounds: i32,
+    /// Plays with the Kraken, White Whale and Loot cards.
+    pub expansion: bool,
+    /// Cards in the deck, the server's default deck when not given.
+    pub deck: Option<DeckConfig>,
+    /// Adds a Yellow suit to the deck.
+    pub fourth_suit: bool,
+    /// Ends the game early when the deck cannot deal `rounds` cards to
+    /// every seat.
+    pub scale_rounds: bool,
+    /// Deals from this seed instead of a random one, to replay the same
+    /// cards. Only its presence is listed, the seed itself is revealed in the
+    /// log once the game is finished.
+    pub seed: Option<u64>,
+}
+
+impl Default for RuleOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RuleOptions {
+    pub const fn new() -> Self {
+        RuleOptions {
+            rounds: LAST_ROUND,
+            expansion: false,
+            deck: None,
+            fourth_suit: false,
+            scale_rounds: false,
+            seed: None,
+        }
+    }
+
+    /// Full deck for a game under these rules.
+    pub fn deck(&self) -> Result<Deck, GameError> {
+        let mut config = self.deck.clone().unwrap_or_default();
+        if self.fourth_suit && !config.colors.contains(&CardColor::Yellow) {
+            config.colors.push(CardColor::Yellow);
+        }
+        let mut deck = config.create_deck()?;
+        if self.expansion {
+            game::add_expansion_cards(&mut deck)?;
+        }
+        Ok(deck)
+    }
+
+    /// Highest number on the cards, worth a capture bonus.
+    pub fn highest_value(&self) -> i32 {
+        match &self.deck {
+            Some(config) => config.max_value,
+            None => DeckConfig::new().max_value,
+        }
+    }
+
+    /// Last round played by `seats` players: `rounds`, or as many full
+    /// rounds as the deck allows when scaling.
+    pub fn last_round(&self, seats: usize) -> i32 {
+        if !self.scale_rounds || seats == 0 {
+            return self.rounds;
+        }
+        let deck_size = self.deck().map_or(0, |deck| deck.cards.len());
+        let deck_rounds = (deck_size / seats) as i32;
+        self.rounds.min(deck_rounds)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TableSettings {
+    pub name: String,
+    pub max_seats: usize,
+    pub rules: RuleOptions,
+}
+
+impl Default for TableSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TableSettings {
+    pub const fn new() -> Self {
+        TableSettings {
+            name: String::new(),
+            max_seats: DEFAULT_MAX_SEATS,
+            rules: RuleOptions::new(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), GameError> {
+        if !(MIN_PLAYERS..=MAX_SEATS).contains(&self.max_seats) {
+            return Err(GameError::InvalidSettings {
+                reason: "max seats must be between 2 and 8",
+            });
+        }
+        if !(1..=LAST_ROUND).contains(&self.rules.rounds) {
+            return Err(GameError::InvalidSettings {
+                reason: "rounds must be between 1 and 10",
+            });
+        }
+        let deck_size = self.rules.deck()?.cards.len();
+        if self.rules.last_round(self.max_seats) < 1 {
+            return Err(GameError::InvalidSettings {
+                reason: "not enough cards to deal a single round to every seat",
+            });
+        }
+        let needed = self.max_seats * self.rules.last_round(self.max_seats) as usize;
+        if deck_size < needed {
+            return Err(GameError::InvalidSettings {
+                reason: "not enough cards to deal the last round to every seat",
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A table as listed in the lobby.
+#[derive(Debug, Clone, Serialize)]
+pub struct TableSummary {
+    pub table_id: String,
+    pub name: String,
+    pub phase: GamePhase,
+    pub seats_taken: usize,
+    pub max_seats: usize,
+    pub host_id: Option<i32>,
+    /// Set when the cards are dealt from a fixed seed. The seed stays hidden,
+    /// since it gives away every hand.
+    pub seeded: bool,
+}
+
+impl Game {
+    pub fn with_settings(settings: TableSettings) -> Result<Self, GameError> {
+        settings.validate()?;
+        let mut game = Game::new();
+        game.settings = settings;
+        Ok(game)
+    }
+
+    pub fn summary(&self, table_id: &str) -> TableSummary {
+        TableSummary {
+            table_id: table_id.to_string(),
+            name: self.settings.name.clone(),
+            phase: self.phase,
+            seats_taken: self.table.seats.len(),
+            max_seats: self.settings.max_seats,
+            host_id: self.host_id(),
+            seeded: self.settings.rules.seed.is_some(),
+        }
+    }
+
+    /// The longest seated human player hosts the table. Player ids only
+    /// grow, so that is the lowest one, whatever order the seats are in.
+    pub fn host_id(&self) -> Option<i32> {
+        self.table
+            .seats
+            .iter()
+            .map(|seat| seat.player.player_id)
+            .filter(|&player_id| self.bots.iter().all(|bot| bot.player_id != player_id))
+            .min()
+    }
+
+    pub fn add_player(&mut self, name: String) -> Result<i32, GameError> {
+        self.seat_player(name, None)
+    }
+
+    /// Seats a human player, or a bot of the given kind.
+    pub(crate) fn seat_player(
+        &mut self,
+        name: String,
+        bot: Option<BotKind>,
+    ) -> Result<i32, GameError> {
+        self.expect_phase(GamePhase::Lobby)?;
+        if self.table.seats.len() >= self.settings.max_seats {
+            return Err(GameError::TableFull);
+        }
+        self.next_player_id += 1;
+        let player_id = self.next_player_id;
+        self.table.seats.push(online_board::new_seat(Player {
+            name: name.clone(),
+            player_id,
+        }));
+        self.table.seat_count = self.table.seats.len() as i32;
+        if let Some(kind) = bot {
+            self.bots.push(BotSeat { player_id, kind });
+        }
+        self.log.push(GameEvent::PlayerJoined {
+            player_id,
+            name: name.clone(),
+            bot,
+        });
+        self.events
+            .push(TableEvent::PlayerJoined { player_id, name });
+        Ok(player_id)
+    }
+
+    pub fn leave(&mut self, player_id: i32) -> Result<(), GameError> {
+        self.expect_phase(GamePhase::Lobby)?;
+        let seat = self.seat_of(player_id).ok_or(GameError::UnknownPlayer)?;
+        self.table.seats.remove(seat);
+        self.table.seat_count = self.table.seats.len() as i32;
+        self.close_session(player_id);
+        self.bots.retain(|bot| bot.player_id != player_id);
+        self.log.push(GameEvent::PlayerLeft { player_id });
+        self.events.push(TableEvent::PlayerLeft { player_id });
+        Ok(())
+    }
+
+    pub fn kick(&mut self, host_id: i32, player_id: i32) -> Result<(), GameError> {
+        if self.host_id() != Some(host_id) {
+            return Err(GameError::NotHost);
+        }
+        self.leave(player_id)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_lobby_seats() {
+    let settings = TableSettings {
+        name: "friday".to_string(),
+        max_seats: 3,
+        rules: RuleOptions {
+            rounds: 2,
+            ..RuleOptions::new()
+        },
+    };
+    let mut game = Game::with_settings(settings).unwrap();
+    game.seat_players(3);
+    assert_eq!(
+        game.add_player("dave".to_string()),
+        Err(GameError::TableFull)
+    );
+
+    assert_eq!(game.kick(2, 3), Err(GameError::NotHost));
+    game.kick(1, 3).unwrap();
+    game.leave(1).unwrap();
+    assert_eq!(game.host_id(), Some(2));
+    assert_eq!(game.leave(1), Err(GameError::UnknownPlayer));
+    assert_eq!(game.add_player("dave".to_string()), Ok(4));
+
+    let summary = game.summary("abc");
+    assert_eq!(summary.seats_taken, 2);
+    assert_eq!(summary.max_seats, 3);
+    assert!(!summary.seeded);
+
+    game.start().unwrap();
+    // shuffling the seats at start does not hand the table to someone else
+    assert_eq!(game.host_id(), Some(2));
+    assert_eq!(game.leave(2), Err(GameError::WrongPhase));
+    assert!(Game::with_settings(TableSettings {
+        max_seats: 9,
+        ..TableSettings::new()
+    })
+    .is_err());
+    assert!(Game::with_settings(TableSettings {
+        max_seats: 8,
+        ..TableSettings::new()
+    })
+    .is_err());
+    let small_deck = DeckConfig {
+        max_value: 5,
+        ..DeckConfig::new()
+    };
+    assert_eq!(
+        Game::with_settings(TableSettings {
+            rules: RuleOptions {
+                deck: Some(small_deck),
+                ..RuleOptions::new()
+            },
+            ..TableSettings::new()
+        })
+        .err(),
+        Some(GameError::InvalidSettings {
+            reason: "not enough cards to deal the last round to every seat"
+        })
+    );
+}
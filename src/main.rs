@@ -1,27 +1,87 @@
 #[macro_use]
 extern crate rocket;
 
-mod game;
-mod online_board;
-mod user;
-
-use game::*;
-use online_board::*;
-use rand::rng;
-use rand::seq::SliceRandom;
+use cards_online::bot::BotKind;
+use cards_online::error::GameError;
+use cards_online::game::*;
+use cards_online::game_log::GameEvent;
+use cards_online::lifecycle::{Game, GamePhase};
+use cards_online::lobby::{TableSettings, TableSummary};
+use cards_online::online_board::{self, *};
+use cards_online::registry::TableRegistry;
+use cards_online::scoring::Scoreboard;
+use cards_online::storage::TableStore;
+use cards_online::user::*;
+use cards_online::view::*;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::status::Custom;
+use rocket::response::stream::{Event, EventStream};
+use rocket::serde::json::Json;
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::error::RecvError;
+use rocket::{Shutdown, State};
+use serde::Serialize;
 use std::io::{self, Write};
-use std::sync::Mutex;
-use std::vec;
-use user::*;
-
-static GAMEBOARD: Mutex<Table> = Mutex::new(Table {
-    seats: Vec::new(),
-    river: Vec::new(),
-    deck: Deck::default(),
-    seat_count: 0,
-});
-static IS_GAME_STARTED: Mutex<bool> = Mutex::new(false);
 
+type ApiResult<T> = Result<Json<T>, Custom<String>>;
+
+fn refused(error: GameError) -> Custom<String> {
+    let status = match error {
+        GameError::UnknownTable | GameError::UnknownPlayer => Status::NotFound,
+        GameError::Unauthorized => Status::Unauthorized,
+        GameError::NotHost => Status::Forbidden,
+        GameError::NotYourTurn
+        | GameError::WrongPhase
+        | GameError::AlreadyBid
+        | GameError::TableFull
+        | GameError::NotEnoughPlayers => Status::Conflict,
+        GameError::CardNotInHand
+        | GameError::IllegalCard { .. }
+        | GameError::InvalidBid { .. }
+        | GameError::MissingChoice
+        | GameError::NoChoice => Status::UnprocessableEntity,
+        GameError::InvalidSettings { .. }
+        | GameError::SettingTooHigh { .. }
+        | GameError::InvalidCard => Status::BadRequest,
+        GameError::DeckExhausted | GameError::CardsNotConserved | GameError::BotFailed => {
+            Status::InternalServerError
+        }
+    };
+    Custom(status, error.to_string())
+}
+
+fn bad_request(message: String) -> Custom<String> {
+    Custom(Status::BadRequest, message)
+}
+
+/// Session token handed out on join, sent back as `Authorization: Bearer`.
+struct SessionToken(String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SessionToken {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request
+            .headers()
+            .get_one("Authorization")
+            .and_then(|header| header.strip_prefix("Bearer "))
+        {
+            Some(token) => Outcome::Success(SessionToken(token.to_string())),
+            None => Outcome::Error((Status::Unauthorized, "missing bearer token")),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JoinedTable {
+    #[serde(flatten)]
+    player: Player,
+    token: String,
+}
+
+#[allow(dead_code)]
 fn console_test_run() {
     println!("Hello, welcome to card online");
 
@@ -40,7 +100,7 @@ fn console_test_run() {
 
     println!("Hello, you will be playing with {} players!", player_count);
 
-    let mut table = new_table(player_count);
+    let mut table = new_table(player_count).expect("could not create the table");
 
     for i in 1..=player_count {
         print!("Please enter the name of player n°{}: ", i);
@@ -57,13 +117,13 @@ fn console_test_run() {
         }));
     }
 
-    table.deck = create_default_deck();
+    table.deck = create_default_deck().expect("could not create the deck");
 
     println!("Fresh Table:\n{}", table);
 
     // Shuffle the deck
-    let mut rng = rng();
-    table.deck.cards.shuffle(&mut rng);
+    let mut rng = table.next_rng();
+    table.deck.shuffle(&mut rng);
 
     println!("Shuffled deck Table:\n{}", table);
 
@@ -80,18 +140,22 @@ fn console_test_run() {
     }
 
     for seat in table.seats.iter_mut() {
-        if let Some(card) = seat.hand.pop() {
-            online_board::play_card(&mut table.river, &seat.player, card);
+        if let Some(&card_index) = legal_plays(&table.river, &seat.hand).first() {
+            online_board::play_card(&mut table.river, seat, card_index, Some(CardEffect::Pirate))
+                .unwrap();
         }
     }
 
-    store_winner_fold(&mut table, 1);
+    if let Some(winner) = resolve_trick(&mut table, 14).unwrap() {
+        println!("Player {} wins the trick", winner);
+    }
 
     println!("first game Table:\n{}", table);
 
     for seat in table.seats.iter_mut() {
-        if let Some(card) = seat.hand.pop() {
-            online_board::play_card(&mut table.river, &seat.player, card);
+        if let Some(&card_index) = legal_plays(&table.river, &seat.hand).first() {
+            online_board::play_card(&mut table.river, seat, card_index, Some(CardEffect::Pirate))
+                .unwrap();
         }
     }
 
@@ -108,54 +172,366 @@ fn index() -> &'static str {
     "Hello, world!"
 }
 
-#[get("/AddPlayer/<name>")]
-fn add_player(name: String) -> String {
-    let mut game_board = GAMEBOARD.lock().unwrap();
-    let player_id = game_board.seat_count + 1;
-    game_board.seats.push(new_seat(Player {
-        name: name.clone(),
-        player_id: player_id,
-    }));
-    game_board.seat_count += 1;
-    format!("Player {} added to the table", name)
+#[post("/tables", data = "<settings>")]
+fn create_table(
+    registry: &State<TableRegistry>,
+    default_deck: &State<DeckConfig>,
+    settings: Json<TableSettings>,
+) -> ApiResult<TableSummary> {
+    let mut settings = settings.into_inner();
+    if settings.rules.deck.is_none() {
+        settings.rules.deck = Some(default_deck.inner().clone());
+    }
+    let table_id = registry.create(settings).map_err(refused)?;
+    let summary = registry
+        .read_game(&table_id, |game| Ok(game.summary(&table_id)))
+        .map_err(refused)?;
+    Ok(Json(summary))
+}
+
+#[get("/tables")]
+fn list_tables(registry: &State<TableRegistry>) -> Json<Vec<TableSummary>> {
+    Json(registry.list())
+}
+
+#[post("/tables/<table_id>/Join/<name>")]
+fn add_player(
+    registry: &State<TableRegistry>,
+    table_id: &str,
+    name: String,
+) -> ApiResult<JoinedTable> {
+    let (player_id, token) = registry
+        .with_game(table_id, |game| game.join(name.clone()))
+        .map_err(refused)?;
+    Ok(Json(JoinedTable {
+        player: Player { name, player_id },
+        token,
+    }))
+}
+
+#[post("/tables/<table_id>/Leave")]
+fn leave(
+    registry: &State<TableRegistry>,
+    table_id: &str,
+    token: SessionToken,
+) -> ApiResult<TableSummary> {
+    let summary = registry
+        .with_game(table_id, |game| {
+            let player_id = game.authenticate(&token.0)?;
+            game.leave(player_id)?;
+            Ok(game.summary(table_id))
+        })
+        .map_err(refused)?;
+    Ok(Json(summary))
+}
+
+#[post("/tables/<table_id>/Kick/<player_id>")]
+fn kick(
+    registry: &State<TableRegistry>,
+    table_id: &str,
+    token: SessionToken,
+    player_id: i32,
+) -> ApiResult<TableSummary> {
+    let summary = registry
+        .with_game(table_id, |game| {
+            let host_id = game.authenticate(&token.0)?;
+            game.kick(host_id, player_id)?;
+            Ok(game.summary(table_id))
+        })
+        .map_err(refused)?;
+    Ok(Json(summary))
+}
+
+/// The host fills a seat with a `random` or `heuristic` bot.
+#[post("/tables/<table_id>/AddBot/<kind>")]
+fn add_bot(
+    registry: &State<TableRegistry>,
+    table_id: &str,
+    token: SessionToken,
+    kind: &str,
+) -> ApiResult<Player> {
+    let kind: BotKind = kind.parse().map_err(bad_request)?;
+    let player = registry
+        .with_game(table_id, |game| {
+            if game.host_id() != Some(game.authenticate(&token.0)?) {
+                return Err(GameError::NotHost);
+            }
+            let player_id = game.add_bot(kind)?;
+            let seat = game.seat_of(player_id).ok_or(GameError::UnknownPlayer)?;
+            Ok(game.table.seats[seat].player.clone())
+        })
+        .map_err(refused)?;
+    Ok(Json(player))
+}
+
+/// Starts the game, dealt from the table's `seed` rule when it has one.
+#[post("/tables/<table_id>/StartGame")]
+fn start_game(
+    registry: &State<TableRegistry>,
+    table_id: &str,
+    token: SessionToken,
+) -> ApiResult<TableView> {
+    let view = registry
+        .act(
+            table_id,
+            |game| {
+                if game.host_id() != Some(game.authenticate(&token.0)?) {
+                    return Err(GameError::NotHost);
+                }
+                game.start()
+            },
+            |game, ()| Ok(game.table_view()),
+        )
+        .map_err(refused)?;
+    Ok(Json(view))
 }
 
-#[get("/StartGame")]
-fn start_game() -> String {
-    let mut is_game_started = IS_GAME_STARTED.lock().unwrap();
-    if *is_game_started {
-        return "Game already started".to_string();
+#[post("/tables/<table_id>/Bid/<bid>")]
+fn bid(
+    registry: &State<TableRegistry>,
+    table_id: &str,
+    token: SessionToken,
+    bid: i32,
+) -> ApiResult<PlayerView> {
+    let view = registry
+        .act(
+            table_id,
+            |game| {
+                let player_id = game.authenticate(&token.0)?;
+                game.bid(player_id, bid)?;
+                Ok(player_id)
+            },
+            |game, player_id| game.player_view(player_id),
+        )
+        .map_err(refused)?;
+    Ok(Json(view))
+}
+
+#[get("/tables/<table_id>/Bids")]
+fn bids(registry: &State<TableRegistry>, table_id: &str) -> ApiResult<Option<Vec<BidView>>> {
+    let bids = registry
+        .read_game(table_id, |game| Ok(game.table_view().bids))
+        .map_err(refused)?;
+    Ok(Json(bids))
+}
+
+#[derive(Serialize)]
+struct PlayResponse {
+    /// Winner of the trick the card went into, once it is closed, even when
+    /// a bot played its last card.
+    trick_winner: Option<i32>,
+    #[serde(flatten)]
+    view: PlayerView,
+}
+
+fn parse_choice(choice: Option<&str>) -> Result<Option<CardEffect>, Custom<String>> {
+    match choice {
+        None => Ok(None),
+        Some("pirate") => Ok(Some(CardEffect::Pirate)),
+        Some("flag") => Ok(Some(CardEffect::Flag)),
+        Some(other) => Err(bad_request(format!(
+            "Unknown choice {}, use pirate or flag",
+            other
+        ))),
     }
-    *is_game_started = true;
+}
 
-    let mut game_board = GAMEBOARD.lock().unwrap();
-    // create a new deck
-    game_board.deck = create_default_deck();
+#[post("/tables/<table_id>/PlayCard/<card_index>?<choice>")]
+fn play(
+    registry: &State<TableRegistry>,
+    table_id: &str,
+    token: SessionToken,
+    card_index: usize,
+    choice: Option<&str>,
+) -> ApiResult<PlayResponse> {
+    let choice = parse_choice(choice)?;
+
+    let response = registry
+        .act(
+            table_id,
+            |game| {
+                let player_id = game.authenticate(&token.0)?;
+                let step = game.log.len();
+                game.play(player_id, card_index, choice)?;
+                Ok((player_id, step))
+            },
+            |game, (player_id, step)| {
+                Ok(PlayResponse {
+                    trick_winner: game.trick_winner_since(step),
+                    view: game.player_view(player_id)?,
+                })
+            },
+        )
+        .map_err(refused)?;
+    Ok(Json(response))
+}
 
-    // Shuffle the deck
-    let mut rng = rng();
-    game_board.deck.cards.shuffle(&mut rng);
+/// Same as `play`, naming the card by its id rather than its place in hand.
+#[post("/tables/<table_id>/PlayCardId/<card_id>?<choice>")]
+fn play_by_id(
+    registry: &State<TableRegistry>,
+    table_id: &str,
+    token: SessionToken,
+    card_id: CardId,
+    choice: Option<&str>,
+) -> ApiResult<PlayResponse> {
+    let choice = parse_choice(choice)?;
+
+    let response = registry
+        .act(
+            table_id,
+            |game| {
+                let player_id = game.authenticate(&token.0)?;
+                let step = game.log.len();
+                game.play_by_id(player_id, card_id, choice)?;
+                Ok((player_id, step))
+            },
+            |game, (player_id, step)| {
+                Ok(PlayResponse {
+                    trick_winner: game.trick_winner_since(step),
+                    view: game.player_view(player_id)?,
+                })
+            },
+        )
+        .map_err(refused)?;
+    Ok(Json(response))
+}
 
-    // shuffle the seats
-    game_board.seats.shuffle(&mut rng);
+#[get("/tables/<table_id>/View")]
+fn view(
+    registry: &State<TableRegistry>,
+    table_id: &str,
+    token: SessionToken,
+) -> ApiResult<PlayerView> {
+    let view = registry
+        .read_game(table_id, |game| {
+            let player_id = game.authenticate(&token.0)?;
+            game.player_view(player_id)
+        })
+        .map_err(refused)?;
+    Ok(Json(view))
+}
 
-    // Collect cards to distribute
-    let mut cards = Vec::new();
-    for _ in 0..game_board.seats.len() {
-        if let Some(card) = game_board.deck.cards.pop() {
-            cards.push(card);
+/// Server-sent events of everything happening at the table.
+#[get("/tables/<table_id>/Events")]
+fn events(
+    registry: &State<TableRegistry>,
+    table_id: &str,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], Custom<String>> {
+    let mut watcher = registry.subscribe(table_id).map_err(refused)?;
+    Ok(EventStream! {
+        loop {
+            let event = select! {
+                event = watcher.recv() => match event {
+                    Ok(event) => event,
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(_)) => continue,
+                },
+                _ = &mut shutdown => break,
+            };
+            yield Event::json(&event);
         }
-    }
+    })
+}
 
-    // Distribute one card to each player for the first round
-    for (seat, card) in game_board.seats.iter_mut().zip(cards) {
-        seat.hand.push(card);
-    }
+#[get("/tables/<table_id>/Scores")]
+fn scores(registry: &State<TableRegistry>, table_id: &str) -> ApiResult<Scoreboard> {
+    let scoreboard = registry
+        .read_game(table_id, |game| Ok(game.table.scoreboard.clone()))
+        .map_err(refused)?;
+    Ok(Json(scoreboard))
+}
+
+/// Every step of a finished game, for post-game review.
+#[get("/tables/<table_id>/Log")]
+fn game_log(registry: &State<TableRegistry>, table_id: &str) -> ApiResult<Vec<GameEvent>> {
+    let log = registry
+        .read_game(table_id, |game| {
+            if game.phase != GamePhase::Finished {
+                return Err(GameError::WrongPhase);
+            }
+            Ok(game.log.clone())
+        })
+        .map_err(refused)?;
+    Ok(Json(log))
+}
+
+/// The whole table of a finished game as it was after `step` log entries.
+#[get("/tables/<table_id>/Replay/<step>")]
+fn replay(registry: &State<TableRegistry>, table_id: &str, step: usize) -> ApiResult<Table> {
+    let game = registry
+        .read_game(table_id, |game| {
+            if game.phase != GamePhase::Finished {
+                return Err(GameError::WrongPhase);
+            }
+            let step = step.min(game.log.len());
+            Game::replay(game.settings.clone(), &game.log[..step])
+        })
+        .map_err(refused)?;
+    Ok(Json(game.table))
+}
 
-    format!("Game started\n{}", game_board)
+#[post("/tables/<table_id>/NextRound")]
+fn next_round(
+    registry: &State<TableRegistry>,
+    table_id: &str,
+    token: SessionToken,
+) -> ApiResult<TableView> {
+    let view = registry
+        .act(
+            table_id,
+            |game| {
+                game.authenticate(&token.0)?;
+                game.next_round()
+            },
+            |game, ()| Ok(game.table_view()),
+        )
+        .map_err(refused)?;
+    Ok(Json(view))
 }
 
 #[launch]
 fn rocket() -> _ {
-    rocket::build().mount("/", routes![index, add_player, start_game])
+    let rocket = rocket::build();
+    let storage_dir: String = rocket
+        .figment()
+        .extract_inner("storage_dir")
+        .unwrap_or_else(|_| "saved_tables".to_string());
+    // [default.deck] in Rocket.toml, for tables created without their own
+    let default_deck: DeckConfig = match rocket.figment().find_value("deck") {
+        Ok(_) => rocket
+            .figment()
+            .extract_inner("deck")
+            .expect("could not read the deck config"),
+        Err(_) => DeckConfig::new(),
+    };
+    default_deck.validate().expect("invalid deck config");
+    let registry = TableStore::new(&storage_dir)
+        .and_then(TableRegistry::with_store)
+        .expect("could not load saved tables");
+
+    rocket.manage(registry).manage(default_deck).mount(
+        "/",
+        routes![
+            index,
+            create_table,
+            list_tables,
+            add_player,
+            leave,
+            kick,
+            add_bot,
+            start_game,
+            bid,
+            bids,
+            play,
+            play_by_id,
+            view,
+            events,
+            scores,
+            game_log,
+            replay,
+            next_round
+        ],
+    )
 }
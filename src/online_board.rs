@@ -1,47 +1,234 @@
 use game::PlayCard;
-use rand::{rng, seq::SliceRandom};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    game::{self, Deck},
+    error::GameError,
+    game::{self, Card, CardColor, CardEffect, CardType, Deck, TrickOutcome},
+    scoring::{self, Scoreboard},
     user::Player,
 };
 use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Seat {
     pub player: Player,
     pub hand: Vec<PlayCard>,
     pub plis: Vec<PlayCard>,
+    pub bid: Option<i32>,
+    pub tricks_won: i32,
+    pub bonus: i32,
+    /// `player_id`s this seat is allied with through a Loot this round.
+    #[serde(default)]
+    pub alliances: Vec<i32>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayedCard {
     order: i32,
     player_id: i32,
     card: PlayCard,
 }
 
-pub fn play_card(table_river: &mut Vec<PlayedCard>, player: &Player, card: PlayCard) {
+impl PlayedCard {
+    pub fn order(&self) -> i32 {
+        self.order
+    }
+
+    pub fn player_id(&self) -> i32 {
+        self.player_id
+    }
+
+    pub fn card(&self) -> &PlayCard {
+        &self.card
+    }
+}
+
+/// Suit led in this trick: the first non-special card in the river.
+pub fn lead_suit(table_river: &[PlayedCard]) -> Option<CardColor> {
+    let mut played_cards: Vec<&PlayedCard> = table_river.iter().collect();
+    played_cards.sort_by_key(|played_card| played_card.order);
+    played_cards
+        .into_iter()
+        .find_map(|played_card| played_card.card.card_type.suit())
+}
+
+/// Specials can always be played; a colored card must follow the lead suit
+/// unless the hand holds none of it.
+pub fn check_legal_play(
+    table_river: &[PlayedCard],
+    hand: &[PlayCard],
+    card: &PlayCard,
+) -> Result<(), GameError> {
+    let Some(lead) = lead_suit(table_river) else {
+        return Ok(());
+    };
+    match card.card_type.suit() {
+        None => Ok(()),
+        Some(suit) if suit == lead => Ok(()),
+        Some(_) if hand.iter().any(|held| held.card_type.suit() == Some(lead)) => {
+            Err(GameError::IllegalCard { lead })
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+/// Indexes of the cards in `hand` that may be played on this river.
+pub fn legal_plays(table_river: &[PlayedCard], hand: &[PlayCard]) -> Vec<usize> {
+    (0..hand.len())
+        .filter(|&index| check_legal_play(table_river, hand, &hand[index]).is_ok())
+        .collect()
+}
+
+/// Moves a card from the seat's hand to the river. A MarySue needs the
+/// player's `choice` of playing it as a Pirate or as a Flag; the choice is
+/// ignored for every other card.
+pub fn play_card(
+    table_river: &mut Vec<PlayedCard>,
+    seat: &mut Seat,
+    card_index: usize,
+    choice: Option<CardEffect>,
+) -> Result<(), GameError> {
+    let card = seat.hand.get(card_index).ok_or(GameError::CardNotInHand)?;
+    check_legal_play(table_river, &seat.hand, card)?;
+    if card.card_type.is_mary_sue() && choice.is_none() {
+        return Err(GameError::MissingChoice);
+    }
+
+    let mut card = *card;
+    if let (CardType::MarySue(mary_sue), Some(choice)) = (&mut card.card_type, choice) {
+        mary_sue.set_card_effect(choice)?;
+    }
+    seat.hand.remove(card_index);
     let played_card = PlayedCard {
         order: table_river.len() as i32,
-        player_id: player.player_id,
+        player_id: seat.player.player_id,
         card,
     };
     table_river.push(played_card);
+    Ok(())
+}
+
+/// Walks the river in play order and tells how the trick ends, with the
+/// `player_id` of the seat taking it or leading after it is destroyed.
+fn trick_outcome(table_river: &[PlayedCard]) -> Option<(TrickOutcome, i32)> {
+    let mut played_cards: Vec<&PlayedCard> = table_river.iter().collect();
+    played_cards.sort_by_key(|played_card| played_card.order);
+
+    let trick: Vec<CardType> = played_cards
+        .iter()
+        .map(|played_card| played_card.card.card_type)
+        .collect();
+    let outcome = game::trick_outcome(&trick)?;
+    Some((outcome, played_cards[outcome.leader()].player_id))
 }
 
-pub fn store_winner_fold(table: &mut Table, player_id: i32) {
-    let mut winner_cards = Vec::new();
+/// `player_id` of the seat taking the trick, or `None` when nothing has been
+/// played yet or the trick is destroyed.
+pub fn trick_winner(table_river: &[PlayedCard]) -> Option<i32> {
+    match trick_outcome(table_river)? {
+        (TrickOutcome::Won(_), player_id) => Some(player_id),
+        (TrickOutcome::Destroyed { .. }, _) => None,
+    }
+}
+
+/// `player_id` of the seat leading the next trick, whether the river is
+/// won or destroyed.
+pub fn next_leader(table_river: &[PlayedCard]) -> Option<i32> {
+    trick_outcome(table_river).map(|(_, player_id)| player_id)
+}
+
+/// Gives the river to whoever won it, counting the trick, its capture
+/// bonus and any Loot alliance for them, and returns their `player_id`. A
+/// destroyed trick goes to the discard and nobody is returned. Cards of
+/// the deck's `highest_value` earn a capture bonus.
+pub fn resolve_trick(table: &mut Table, highest_value: i32) -> Result<Option<i32>, GameError> {
+    let Some(winner) = trick_winner(&table.river) else {
+        let destroyed = table.river.drain(..).map(|played_card| played_card.card);
+        table.discard.extend(destroyed);
+        return Ok(None);
+    };
+    let winning_card = table
+        .river
+        .iter()
+        .find(|played_card| played_card.player_id == winner)
+        .ok_or(GameError::UnknownPlayer)?
+        .card
+        .card_type;
+    let trick: Vec<CardType> = table
+        .river
+        .iter()
+        .map(|played_card| played_card.card.card_type)
+        .collect();
+    let bonus = scoring::capture_bonus(&winning_card, &trick, highest_value);
+    let allies: Vec<i32> = table
+        .river
+        .iter()
+        .filter(|played_card| {
+            matches!(played_card.card.card_type, CardType::Loot(_))
+                && played_card.player_id != winner
+        })
+        .map(|played_card| played_card.player_id)
+        .collect();
+
+    store_winner_fold(table, winner)?;
+    for seat in table.seats.iter_mut() {
+        if seat.player.player_id == winner {
+            seat.tricks_won += 1;
+            seat.bonus += bonus;
+            seat.alliances.extend(&allies);
+        } else if allies.contains(&seat.player.player_id) {
+            seat.alliances.push(winner);
+        }
+    }
+    Ok(Some(winner))
+}
+
+/// Moves the river into the plis of `player_id`, refusing a player who is
+/// not seated rather than losing the cards.
+pub fn store_winner_fold(table: &mut Table, player_id: i32) -> Result<(), GameError> {
+    let seat = table
+        .seats
+        .iter_mut()
+        .find(|seat| seat.player.player_id == player_id)
+        .ok_or(GameError::UnknownPlayer)?;
     while let Some(card) = table.river.pop() {
-        winner_cards.push(card.card);
+        seat.plis.push(card.card);
     }
-    if let Some(seat) = table
+    Ok(())
+}
+
+/// Records a hidden bid for the round; a seat bids once, between 0 and the
+/// round number.
+pub fn place_bid(table: &mut Table, player_id: i32, bid: i32) -> Result<(), GameError> {
+    if !(0..=table.round).contains(&bid) {
+        return Err(GameError::InvalidBid { max: table.round });
+    }
+    let seat = table
         .seats
         .iter_mut()
         .find(|seat| seat.player.player_id == player_id)
-    {
-        seat.plis.extend(winner_cards);
+        .ok_or(GameError::UnknownPlayer)?;
+    if seat.bid.is_some() {
+        return Err(GameError::AlreadyBid);
     }
+    seat.bid = Some(bid);
+    Ok(())
+}
+
+pub fn all_bids_placed(table: &Table) -> bool {
+    !table.seats.is_empty() && table.seats.iter().all(|seat| seat.bid.is_some())
+}
+
+/// Every `(player_id, bid)` pair once the last bid is in, `None` while some
+/// seats are still bidding so nobody sees the others early.
+pub fn revealed_bids(table: &Table) -> Option<Vec<(i32, i32)>> {
+    table
+        .seats
+        .iter()
+        .map(|seat| Some((seat.player.player_id, seat.bid?)))
+        .collect()
 }
 
 pub fn clear_table_after_game(table: &mut Table) {
@@ -51,27 +238,88 @@ pub fn clear_table_after_game(table: &mut Table) {
     for seat in table.seats.iter_mut() {
         all_cards.append(&mut seat.hand);
         all_cards.append(&mut seat.plis);
+        seat.bid = None;
+        seat.tricks_won = 0;
+        seat.bonus = 0;
+        seat.alliances.clear();
     }
+    all_cards.append(&mut table.discard);
 
     // Retrieve cards from river if any are left
     while let Some(played_card) = table.river.pop() {
         all_cards.push(played_card.card);
     }
 
+    // MarySue is chosen again each time she is played
+    for card in all_cards.iter_mut() {
+        if let CardType::MarySue(mary_sue) = &mut card.card_type {
+            mary_sue.choice = None;
+        }
+    }
+
     // Put all cards back in the deck
     table.deck.cards.append(&mut all_cards);
 
     // Shuffle the deck
-    let mut rng = rng();
-    table.deck.cards.shuffle(&mut rng);
+    let mut rng = table.next_rng();
+    table.deck.shuffle(&mut rng);
+}
+
+/// Confirms each card of a deck of `card_count` cards sits in exactly one
+/// place: the deck, a hand, a plis, the river or the discard.
+pub fn check_cards(table: &Table, card_count: usize) -> Result<(), GameError> {
+    let mut seen = vec![false; card_count];
+    let cards = table
+        .deck
+        .cards
+        .iter()
+        .chain(
+            table
+                .seats
+                .iter()
+                .flat_map(|seat| seat.hand.iter().chain(&seat.plis)),
+        )
+        .chain(table.river.iter().map(|played_card| &played_card.card))
+        .chain(&table.discard);
+    for card in cards {
+        match seen.get_mut(card.id as usize) {
+            Some(seen) if !*seen => *seen = true,
+            _ => return Err(GameError::CardsNotConserved),
+        }
+    }
+    if seen.contains(&false) {
+        return Err(GameError::CardsNotConserved);
+    }
+    Ok(())
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Table {
     pub seats: Vec<Seat>,
     pub deck: Deck,
     pub river: Vec<PlayedCard>,
+    /// Tricks destroyed this round, back in the deck for the next one.
+    #[serde(default)]
+    pub discard: Vec<PlayCard>,
     pub seat_count: i32,
+    pub round: i32,
+    pub scoreboard: Scoreboard,
+    /// Every shuffle at this table derives from the seed, so a game can be
+    /// played again card for card.
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub shuffles: u64,
+    /// Cards in play for the whole game, counted when the first round is
+    /// dealt.
+    #[serde(default)]
+    pub card_count: usize,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Table {
@@ -80,52 +328,95 @@ impl Table {
             seats: Vec::new(),
             deck: Deck::default(),
             river: Vec::new(),
+            discard: Vec::new(),
             seat_count: 0,
+            round: 0,
+            scoreboard: Scoreboard::new(),
+            seed: 0,
+            shuffles: 0,
+            card_count: 0,
         }
     }
+
+    /// Random source for the next shuffle at this table. Unlike `StdRng`,
+    /// ChaCha8 gives the same cards for a seed on every build and platform.
+    pub fn next_rng(&mut self) -> ChaCha8Rng {
+        let rng = ChaCha8Rng::seed_from_u64(mix_seed(self.seed, self.shuffles));
+        self.shuffles += 1;
+        rng
+    }
+}
+
+/// SplitMix64 over the seed and the shuffle number, so neighbouring seeds
+/// never share a shuffle.
+fn mix_seed(seed: u64, shuffle: u64) -> u64 {
+    splitmix(splitmix(seed) ^ shuffle.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+fn splitmix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
 
 impl fmt::Display for Seat {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.player);
+        write!(f, "{}", self.player)?;
 
-        write!(f, "\tplis: {} cards\n", self.plis.len());
+        if let Some(bid) = self.bid {
+            writeln!(f, "\tbid: {}", bid)?;
+        }
+        writeln!(
+            f,
+            "\tplis: {} cards ({} tricks)",
+            self.plis.len(),
+            self.tricks_won
+        )?;
         for card in &self.plis {
-            write!(f, "\t\t{}", card);
+            write!(f, "\t\t{}", card)?;
         }
 
-        write!(f, "\thand: {} cards\n", self.hand.len());
+        writeln!(f, "\thand: {} cards", self.hand.len())?;
         // Print each card in the deck
-        Ok(for card in &self.hand {
-            write!(f, "\t\t{}", card);
-        })
+        for card in &self.hand {
+            write!(f, "\t\t{}", card)?;
+        }
+        Ok(())
     }
 }
 
 impl fmt::Display for Table {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "\tDeck:\n\t\t{} cards:\n", self.deck.cards.len());
+        writeln!(f, "\tDeck:\n\t\t{} cards:", self.deck.cards.len())?;
         for card in &self.deck.cards {
-            write!(f, "\t\t\t{}", card);
+            write!(f, "\t\t\t{}", card)?;
         }
-        write!(f, "Seats:\n");
+        writeln!(f, "Seats:")?;
         for seat in &self.seats {
-            write!(f, "{}\n", seat);
+            writeln!(f, "{}", seat)?;
         }
-        write!(f, "River {} cards:\n", self.river.len());
-        Ok(for played_card in &self.river {
-            write!(f, "\t{}", played_card.card);
-        })
+        writeln!(f, "River {} cards:", self.river.len())?;
+        for played_card in &self.river {
+            write!(f, "\t{}", played_card.card)?;
+        }
+        Ok(())
     }
 }
 
-pub fn new_table(player_count: i32) -> Table {
-    Table {
+pub fn new_table(player_count: i32) -> Result<Table, GameError> {
+    Ok(Table {
         seats: Vec::new(),
-        deck: game::create_default_deck(),
+        deck: game::create_default_deck()?,
         seat_count: player_count,
         river: Vec::new(),
-    }
+        discard: Vec::new(),
+        round: 0,
+        scoreboard: Scoreboard::new(),
+        seed: rand::random(),
+        shuffles: 0,
+        card_count: 0,
+    })
 }
 
 pub fn new_seat(player: Player) -> Seat {
@@ -133,5 +424,299 @@ pub fn new_seat(player: Player) -> Seat {
         player,
         hand: Vec::new(),
         plis: Vec::new(),
+        bid: None,
+        tricks_won: 0,
+        bonus: 0,
+        alliances: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_trick_winner() {
+    use game::{CardColor, CardType, ColorCard, PirateCard, SkullCard, WhiteFlagCard};
+
+    let red = |value| {
+        CardType::Color(ColorCard {
+            color: CardColor::Red,
+            value,
+        })
+    };
+    let blue_9 = CardType::Color(ColorCard {
+        color: CardColor::Blue,
+        value: 9,
+    });
+    let river_of = |cards: Vec<CardType>| -> Vec<PlayedCard> {
+        game::with_ids(cards)
+            .into_iter()
+            .enumerate()
+            .map(|(index, card)| PlayedCard {
+                order: index as i32,
+                player_id: index as i32 + 1,
+                card,
+            })
+            .collect()
+    };
+
+    assert_eq!(trick_winner(&[]), None);
+    // off-suit cards never beat the lead color
+    assert_eq!(
+        trick_winner(&river_of(vec![red(3), blue_9, red(7)])),
+        Some(3)
+    );
+    // a flag leading does not set the suit
+    assert_eq!(
+        trick_winner(&river_of(vec![
+            CardType::Flag(WhiteFlagCard {}),
+            blue_9,
+            red(12)
+        ])),
+        Some(2)
+    );
+    // black trumps colors, specials beat black
+    assert_eq!(
+        trick_winner(&river_of(vec![
+            red(13),
+            CardType::Skull(SkullCard { value: 1 }),
+            red(2)
+        ])),
+        Some(2)
+    );
+    assert_eq!(
+        trick_winner(&river_of(vec![
+            CardType::Skull(SkullCard { value: 13 }),
+            CardType::Pirate(PirateCard {}),
+            red(1)
+        ])),
+        Some(2)
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_play_card_follows_suit() {
+    use game::{CardColor, CardType, ColorCard, MermaidCard, SkullCard};
+
+    let color = |color, value| CardType::Color(ColorCard { color, value });
+    let mut river = Vec::new();
+    let mut leader = new_seat(Player {
+        name: "leader".to_string(),
+        player_id: 1,
+    });
+    let mut follower = new_seat(Player {
+        name: "follower".to_string(),
+        player_id: 2,
+    });
+    leader.hand = game::with_ids(vec![color(CardColor::Red, 4)]);
+    follower.hand = game::with_ids(vec![
+        color(CardColor::Blue, 9),
+        CardType::Mermaid(MermaidCard {}),
+        color(CardColor::Red, 2),
+        CardType::Skull(SkullCard { value: 7 }),
+    ]);
+
+    assert_eq!(
+        play_card(&mut river, &mut leader, 3, None),
+        Err(GameError::CardNotInHand)
+    );
+    play_card(&mut river, &mut leader, 0, None).unwrap();
+
+    assert_eq!(legal_plays(&river, &follower.hand), vec![1, 2]);
+    assert_eq!(
+        play_card(&mut river, &mut follower, 0, None),
+        Err(GameError::IllegalCard {
+            lead: CardColor::Red
+        })
+    );
+    assert_eq!(follower.hand.len(), 4);
+    play_card(&mut river, &mut follower, 1, None).unwrap();
+    assert_eq!(river.len(), 2);
+}
+
+/// A default table with `player_count` seated players, numbered from 1.
+#[cfg(test)]
+fn table_with_players(player_count: i32) -> Table {
+    let mut table = new_table(player_count).unwrap();
+    for player_id in 1..=player_count {
+        table.seats.push(new_seat(Player {
+            name: format!("player {}", player_id),
+            player_id,
+        }));
+    }
+    table
+}
+
+#[cfg(test)]
+#[test]
+fn test_bids_hidden_until_all_placed() {
+    let mut table = table_with_players(2);
+    table.round = 2;
+
+    assert_eq!(
+        place_bid(&mut table, 1, 3),
+        Err(GameError::InvalidBid { max: 2 })
+    );
+    assert_eq!(place_bid(&mut table, 7, 0), Err(GameError::UnknownPlayer));
+    place_bid(&mut table, 1, 2).unwrap();
+    assert_eq!(place_bid(&mut table, 1, 1), Err(GameError::AlreadyBid));
+    assert_eq!(revealed_bids(&table), None);
+
+    place_bid(&mut table, 2, 0).unwrap();
+    assert!(all_bids_placed(&table));
+    assert_eq!(revealed_bids(&table), Some(vec![(1, 2), (2, 0)]));
+
+    clear_table_after_game(&mut table);
+    assert_eq!(revealed_bids(&table), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_mary_sue_needs_a_choice() {
+    use game::{CardTypeName, MarySueCard, SkullKingCard};
+
+    let mut river = Vec::new();
+    let mut table = table_with_players(2);
+    let mut cards = game::with_ids(vec![
+        CardType::SkullKing(SkullKingCard {}),
+        CardType::MarySue(MarySueCard { choice: None }),
+    ]);
+    table.seats[1].hand.extend(cards.pop());
+    table.seats[0].hand.extend(cards.pop());
+
+    play_card(&mut river, &mut table.seats[0], 0, None).unwrap();
+    assert_eq!(
+        play_card(&mut river, &mut table.seats[1], 0, None),
+        Err(GameError::MissingChoice)
+    );
+    play_card(&mut river, &mut table.seats[1], 0, Some(CardEffect::Pirate)).unwrap();
+    assert_eq!(
+        river[1].card.card_type.card_type_name(),
+        CardTypeName::Pirate
+    );
+
+    table.river = river;
+    assert_eq!(resolve_trick(&mut table, 14), Ok(Some(1)));
+    // a MarySue played as a Pirate is worth the Skull King bonus
+    assert_eq!(table.seats[0].bonus, 30);
+
+    clear_table_after_game(&mut table);
+    let mary_sue = table
+        .deck
+        .cards
+        .iter()
+        .map(|card| card.card_type)
+        .find(|card| card.is_mary_sue());
+    assert!(matches!(
+        mary_sue,
+        Some(CardType::MarySue(MarySueCard { choice: None }))
+    ));
+}
+
+#[cfg(test)]
+#[test]
+fn test_kraken_and_loot() {
+    use game::{ColorCard, KrakenCard, LootCard, PirateCard};
+
+    let red = |value| {
+        CardType::Color(ColorCard {
+            color: CardColor::Red,
+            value,
+        })
+    };
+    let mut table = table_with_players(3);
+    table.round = 2;
+    let deck_size = table.deck.cards.len();
+    let mut cards = game::with_ids(vec![
+        red(3),
+        CardType::Loot(LootCard {}),
+        CardType::Kraken(KrakenCard {}),
+        red(9),
+        CardType::Pirate(PirateCard {}),
+        red(5),
+    ]);
+    for seat in table.seats.iter_mut().rev() {
+        seat.hand = cards.split_off(cards.len() - 2);
+    }
+
+    for seat in table.seats.iter_mut() {
+        play_card(&mut table.river, seat, 0, None).unwrap();
+    }
+    assert_eq!(next_leader(&table.river), Some(3));
+    assert_eq!(resolve_trick(&mut table, 14), Ok(None));
+    assert_eq!(table.discard.len(), 3);
+    assert!(table.seats.iter().all(|seat| seat.plis.is_empty()));
+
+    for seat in table.seats.iter_mut() {
+        play_card(&mut table.river, seat, 0, None).unwrap();
+    }
+    assert_eq!(
+        store_winner_fold(&mut table, 9),
+        Err(GameError::UnknownPlayer)
+    );
+    assert_eq!(table.river.len(), 3);
+    assert_eq!(resolve_trick(&mut table, 14), Ok(Some(2)));
+    assert_eq!(table.seats[0].alliances, vec![2]);
+    assert_eq!(table.seats[1].alliances, vec![1]);
+
+    for (seat, bid) in table.seats.iter_mut().zip([0, 1, 1]) {
+        seat.bid = Some(bid);
     }
+    // both allies made their bid: 20 each on top of the round score
+    assert_eq!(
+        scoring::score_round(&mut table),
+        vec![(1, 40), (2, 40), (3, -10)]
+    );
+
+    clear_table_after_game(&mut table);
+    assert_eq!(table.deck.cards.len(), deck_size + 6);
+    assert!(table.discard.is_empty());
+    assert!(table.seats[0].alliances.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn test_check_cards() {
+    let mut table = table_with_players(2);
+    for seat in table.seats.iter_mut() {
+        seat.hand.extend(table.deck.cards.pop());
+    }
+    play_card(
+        &mut table.river,
+        &mut table.seats[0],
+        0,
+        Some(CardEffect::Flag),
+    )
+    .unwrap();
+    assert_eq!(check_cards(&table, 70), Ok(()));
+    assert_eq!(check_cards(&table, 71), Err(GameError::CardsNotConserved));
+
+    let lost = table.deck.cards.pop().unwrap();
+    assert_eq!(check_cards(&table, 70), Err(GameError::CardsNotConserved));
+    table.seats[1].plis.push(lost);
+    assert_eq!(check_cards(&table, 70), Ok(()));
+    table.discard.push(lost);
+    assert_eq!(check_cards(&table, 70), Err(GameError::CardsNotConserved));
+}
+
+#[cfg(test)]
+#[test]
+fn test_neighbouring_seeds_shuffle_differently() {
+    use rand::RngCore;
+
+    let mut forty_two = Table {
+        seed: 42,
+        ..Table::default()
+    };
+    let mut forty_three = Table {
+        seed: 43,
+        ..Table::default()
+    };
+    forty_two.next_rng();
+    assert_ne!(
+        forty_two.next_rng().next_u64(),
+        forty_three.next_rng().next_u64()
+    );
+    assert_eq!(forty_two.shuffles, 2);
+    // a saved seed must deal the same cards after any rebuild
+    assert_eq!(Table::default().next_rng().next_u64(), 15996116421076522038);
 }
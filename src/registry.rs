@@ -0,0 +1,347 @@
+use rand::{distr::Alphanumeric, rng, Rng};
+use rocket::tokio::sync::broadcast;
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+
+use crate::{
+    error::GameError,
+    events::TableEvent,
+    lifecycle::{Game, GamePhase},
+    lobby::{TableSettings, TableSummary},
+    storage::TableStore,
+};
+
+pub type TableId = String;
+
+const TABLE_ID_LENGTH: usize = 8;
+/// Events a slow watcher can fall behind before it starts missing some.
+const EVENT_BUFFER: usize = 64;
+
+#[derive(Debug)]
+struct HostedTable {
+    game: Game,
+    watchers: broadcast::Sender<TableEvent>,
+}
+
+/// Every game hosted by the server, keyed by a generated table id. Each
+/// table has its own lock, so a busy table never holds up the others.
+#[derive(Debug, Default)]
+pub struct TableRegistry {
+    tables: RwLock<HashMap<TableId, Mutex<HostedTable>>>,
+    store: Option<TableStore>,
+}
+
+fn host(game: Game) -> Mutex<HostedTable> {
+    let (watchers, _) = broadcast::channel(EVENT_BUFFER);
+    Mutex::new(HostedTable { game, watchers })
+}
+
+impl TableRegistry {
+    pub fn new() -> Self {
+        TableRegistry {
+            tables: RwLock::new(HashMap::new()),
+            store: None,
+        }
+    }
+
+    /// A registry saving every table to `store` after each action, starting
+    /// with the games already saved there. Finished ones are only kept for
+    /// review and are not listed in the lobby.
+    pub fn with_store(store: TableStore) -> std::io::Result<Self> {
+        let tables = store
+            .load_all()?
+            .into_iter()
+            .map(|(table_id, game)| (table_id, host(game)))
+            .collect();
+        Ok(TableRegistry {
+            tables: RwLock::new(tables),
+            store: Some(store),
+        })
+    }
+
+    fn save(&self, table_id: &str, game: &Game) {
+        if let Some(store) = &self.store {
+            if let Err(error) = store.save(table_id, game) {
+                eprintln!("could not save table {}: {}", table_id, error);
+            }
+        }
+    }
+
+    /// Opens a new empty table and returns its id.
+    pub fn create(&self, settings: TableSettings) -> Result<TableId, GameError> {
+        let game = Game::with_settings(settings)?;
+        let mut tables = self.tables.write().unwrap();
+        let table_id = loop {
+            let table_id: TableId = rng()
+                .sample_iter(&Alphanumeric)
+                .take(TABLE_ID_LENGTH)
+                .map(char::from)
+                .collect();
+            if !tables.contains_key(&table_id) {
+                break table_id;
+            }
+        };
+        self.save(&table_id, &game);
+        tables.insert(table_id.clone(), host(game));
+        Ok(table_id)
+    }
+
+    /// Every table whose game is not over yet.
+    pub fn list(&self) -> Vec<TableSummary> {
+        let tables = self.tables.read().unwrap();
+        let mut summaries: Vec<TableSummary> = tables
+            .iter()
+            .filter_map(|(table_id, hosted)| {
+                let hosted = hosted.lock().unwrap();
+                (hosted.game.phase != GamePhase::Finished).then(|| hosted.game.summary(table_id))
+            })
+            .collect();
+        summaries.sort_by(|first, second| first.name.cmp(&second.name));
+        summaries
+    }
+
+    /// Looks at the game of `table_id` without changing it.
+    pub fn read_game<T>(
+        &self,
+        table_id: &str,
+        action: impl FnOnce(&Game) -> Result<T, GameError>,
+    ) -> Result<T, GameError> {
+        let tables = self.tables.read().unwrap();
+        let hosted = tables.get(table_id).ok_or(GameError::UnknownTable)?;
+        let hosted = hosted.lock().unwrap();
+        action(&hosted.game)
+    }
+
+    /// Runs `action` on the game of `table_id` while holding its table, lets
+    /// the bots answer, saves the game and sends whatever it raised to the
+    /// table's watchers. A refused action, one a bot cannot answer or one
+    /// after which a card was lost or duplicated leaves the game as it was
+    /// and raises nothing.
+    pub fn with_game<T>(
+        &self,
+        table_id: &str,
+        action: impl FnOnce(&mut Game) -> Result<T, GameError>,
+    ) -> Result<T, GameError> {
+        self.act(table_id, action, |_, outcome| Ok(outcome))
+    }
+
+    /// Same as `with_game`, building the answer with `respond` once the bots
+    /// have answered, so it shows the game as they left it.
+    pub fn act<A, T>(
+        &self,
+        table_id: &str,
+        action: impl FnOnce(&mut Game) -> Result<A, GameError>,
+        respond: impl FnOnce(&Game, A) -> Result<T, GameError>,
+    ) -> Result<T, GameError> {
+        let tables = self.tables.read().unwrap();
+        let hosted = tables.get(table_id).ok_or(GameError::UnknownTable)?;
+        let mut hosted = hosted.lock().unwrap();
+        let before = hosted.game.clone();
+        let mut outcome = action(&mut hosted.game);
+        if outcome.is_ok() {
+            if let Err(error) = hosted.game.run_bots(&mut rng()) {
+                // the player's action was fine, the server failed them
+                eprintln!("a bot got stuck at table {}: {}", table_id, error);
+                outcome = Err(GameError::BotFailed);
+            } else if let Err(error) = hosted.game.check_cards() {
+                eprintln!("table {} is corrupted: {}", table_id, error);
+                outcome = Err(error);
+            }
+        }
+        let result = outcome.and_then(|outcome| respond(&hosted.game, outcome));
+        match result {
+            Ok(_) => self.save(table_id, &hosted.game),
+            Err(_) => hosted.game = before,
+        }
+        for event in hosted.game.take_events() {
+            // nobody watching is not an error
+            let _ = hosted.watchers.send(event);
+        }
+        result
+    }
+
+    pub fn subscribe(&self, table_id: &str) -> Result<broadcast::Receiver<TableEvent>, GameError> {
+        let tables = self.tables.read().unwrap();
+        let hosted = tables.get(table_id).ok_or(GameError::UnknownTable)?;
+        let hosted = hosted.lock().unwrap();
+        Ok(hosted.watchers.subscribe())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_tables_are_independent() {
+    let registry = TableRegistry::new();
+    let first = registry.create(TableSettings::new()).unwrap();
+    let second = registry.create(TableSettings::new()).unwrap();
+    assert_ne!(first, second);
+
+    registry
+        .with_game(&first, |game| game.add_player("anne".to_string()))
+        .unwrap();
+    let seats = |table_id: &str| registry.read_game(table_id, |game| Ok(game.table.seats.len()));
+    assert_eq!(seats(&first), Ok(1));
+    assert_eq!(seats(&second), Ok(0));
+    assert_eq!(seats("missing"), Err(GameError::UnknownTable));
+    assert_eq!(registry.list().len(), 2);
+
+    let mut watcher = registry.subscribe(&second).unwrap();
+    registry
+        .with_game(&second, |game| game.add_player("bart".to_string()))
+        .unwrap();
+    assert!(matches!(
+        watcher.try_recv(),
+        Ok(TableEvent::PlayerJoined { player_id: 1, .. })
+    ));
+    assert!(watcher.try_recv().is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn test_tables_survive_a_restart() {
+    let dir = std::env::temp_dir().join(format!("cards_online_registry_{}", std::process::id()));
+    let registry = TableRegistry::with_store(TableStore::new(&dir).unwrap()).unwrap();
+    let table_id = registry.create(TableSettings::new()).unwrap();
+    let (_, token) = registry
+        .with_game(&table_id, |game| game.join("anne".to_string()))
+        .unwrap();
+    registry
+        .with_game(&table_id, |game| game.join("bart".to_string()))
+        .unwrap();
+    registry.with_game(&table_id, |game| game.start()).unwrap();
+    let hand = registry
+        .read_game(&table_id, |game| game.player_view(1))
+        .unwrap()
+        .hand;
+    drop(registry);
+
+    let registry = TableRegistry::with_store(TableStore::new(&dir).unwrap()).unwrap();
+    let view = registry
+        .read_game(&table_id, |game| {
+            let player_id = game.authenticate(&token)?;
+            game.player_view(player_id)
+        })
+        .unwrap();
+    assert_eq!(view.table.phase, GamePhase::Bidding);
+    assert_eq!(format!("{:?}", view.hand), format!("{:?}", hand));
+
+    // a game that lost a card is reported and rolled back
+    let table = |registry: &TableRegistry| {
+        registry
+            .read_game(&table_id, |game| Ok(format!("{:?}", game.table)))
+            .unwrap()
+    };
+    let before = table(&registry);
+    let mut watcher = registry.subscribe(&table_id).unwrap();
+    let lost_card = registry.with_game(&table_id, |game| {
+        game.table.deck.cards.pop();
+        game.bid(1, 0)?;
+        game.bid(2, 0)
+    });
+    assert_eq!(lost_card, Err(GameError::CardsNotConserved));
+    assert_eq!(table(&registry), before);
+    assert!(watcher.try_recv().is_err());
+    drop(registry);
+
+    // an unreadable snapshot is skipped, the other tables still load
+    std::fs::write(dir.join("broken.json"), "{ not a game").unwrap();
+    // a finished game is loaded for review but not listed
+    let mut finished = Game::new();
+    finished.phase = GamePhase::Finished;
+    TableStore::new(&dir)
+        .unwrap()
+        .save("finished", &finished)
+        .unwrap();
+    let registry = TableRegistry::with_store(TableStore::new(&dir).unwrap()).unwrap();
+    assert_eq!(
+        registry.read_game(&table_id, |game| game.check_cards()),
+        Ok(())
+    );
+    assert_eq!(
+        registry.read_game("finished", |game| Ok(game.phase)),
+        Ok(GamePhase::Finished)
+    );
+    assert_eq!(registry.list().len(), 1);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(test)]
+#[test]
+fn test_stuck_bot_refuses_the_action() {
+    use crate::bot::BotKind;
+
+    let registry = TableRegistry::new();
+    let table_id = registry.create(TableSettings::new()).unwrap();
+    let human = registry
+        .with_game(&table_id, |game| game.add_player("anne".to_string()))
+        .unwrap();
+    let bot = registry
+        .with_game(&table_id, |game| game.add_bot(BotKind::Heuristic))
+        .unwrap();
+    registry.with_game(&table_id, |game| game.start()).unwrap();
+
+    // the bot leads the trick with no card left to play
+    let stuck = registry.with_game(&table_id, |game| {
+        let bot_seat = game.seat_of(bot).unwrap();
+        let human_seat = game.seat_of(human).unwrap();
+        let cards = std::mem::take(&mut game.table.seats[bot_seat].hand);
+        game.table.seats[human_seat].hand.extend(cards);
+        game.leading_seat = bot_seat;
+        game.bid(human, 0)
+    });
+    assert_eq!(stuck, Err(GameError::BotFailed));
+    assert_eq!(
+        registry.read_game(&table_id, |game| Ok(game.phase)),
+        Ok(GamePhase::Bidding)
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_answers_show_the_bots_moves() {
+    use crate::{bot::BotKind, game::CardEffect, online_board};
+
+    let registry = TableRegistry::new();
+    let table_id = registry.create(TableSettings::new()).unwrap();
+    let human = registry
+        .with_game(&table_id, |game| game.add_player("anne".to_string()))
+        .unwrap();
+    registry
+        .with_game(&table_id, |game| game.add_bot(BotKind::Heuristic))
+        .unwrap();
+    registry.with_game(&table_id, |game| game.start()).unwrap();
+    let fresh_view = || {
+        registry
+            .read_game(&table_id, |game| game.player_view(human))
+            .map(|view| format!("{:?}", view))
+            .unwrap()
+    };
+
+    let view = registry
+        .act(
+            &table_id,
+            |game| game.bid(human, 1),
+            |game, ()| game.player_view(human),
+        )
+        .unwrap();
+    assert_eq!(format!("{:?}", view), fresh_view());
+
+    // the human's card goes into the only trick of the round, whether or
+    // not the bot plays after it
+    let (winner, view) = registry
+        .act(
+            &table_id,
+            |game| {
+                let seat = &game.table.seats[game.seat_of(human).unwrap()];
+                let card_index = online_board::legal_plays(&game.table.river, &seat.hand)[0];
+                let step = game.log.len();
+                game.play(human, card_index, Some(CardEffect::Pirate))?;
+                Ok(step)
+            },
+            |game, step| Ok((game.trick_winner_since(step), game.player_view(human)?)),
+        )
+        .unwrap();
+    assert_eq!(view.table.phase, GamePhase::RoundScoring);
+    assert!(winner.is_some());
+    assert_eq!(format!("{:?}", view), fresh_view());
+}
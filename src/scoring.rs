@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::{CardType, CardTypeName};
+use crate::online_board::Table;
+
+const LOOT_BONUS: i32 = 20;
+
+/// Bonus earned by whoever takes `trick` with `winning_card`: 10 per
+/// colored card of the `highest_value` in the deck, 20 for the black one,
+/// 30 per Pirate taken by the Skull King and 40 for the Skull King taken
+/// by a Mermaid. A MarySue counts as the card she was played as.
+pub fn capture_bonus(winning_card: &CardType, trick: &[CardType], highest_value: i32) -> i32 {
+    let mut bonus = 0;
+    for card in trick {
+        bonus += match (winning_card, card) {
+            (_, CardType::Color(color)) if color.value == highest_value => 10,
+            (_, CardType::Skull(skull)) if skull.value == highest_value => 20,
+            (CardType::SkullKing(_), _) if card.card_type_name() == CardTypeName::Pirate => 30,
+            (CardType::Mermaid(_), CardType::SkullKing(_)) => 40,
+            (_, _) => 0,
+        };
+    }
+    bonus
+}
+
+/// Points for one seat at the end of a round. Bonuses only count when the
+/// bid is made.
+pub fn round_score(bid: i32, tricks_won: i32, bonus: i32, round: i32) -> i32 {
+    if bid == 0 {
+        if tricks_won == 0 {
+            return 10 * round;
+        }
+        return -10 * round;
+    }
+    if bid == tricks_won {
+        return 20 * bid + bonus;
+    }
+    -10 * (bid - tricks_won).abs()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerScore {
+    pub player_id: i32,
+    pub rounds: Vec<i32>,
+    pub total: i32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Scoreboard {
+    pub players: Vec<PlayerScore>,
+}
+
+impl Scoreboard {
+    pub const fn new() -> Self {
+        Scoreboard {
+            players: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, player_id: i32, points: i32) {
+        let position = match self
+            .players
+            .iter()
+            .position(|score| score.player_id == player_id)
+        {
+            Some(position) => position,
+            None => {
+                self.players.push(PlayerScore {
+                    player_id,
+                    rounds: Vec::new(),
+                    total: 0,
+                });
+                self.players.len() - 1
+            }
+        };
+        let score = &mut self.players[position];
+        score.rounds.push(points);
+        score.total += points;
+    }
+
+    pub fn total(&self, player_id: i32) -> i32 {
+        self.players
+            .iter()
+            .find(|score| score.player_id == player_id)
+            .map_or(0, |score| score.total)
+    }
+}
+
+/// Scores every seat for the round being played and adds the result to the
+/// table's scoreboard. Returns the `(player_id, points)` of this round.
+///
+/// Each Loot alliance is worth 20 to both allies when both made their bid.
+pub fn score_round(table: &mut Table) -> Vec<(i32, i32)> {
+    let made_bid = |player_id: i32| {
+        table
+            .seats
+            .iter()
+            .any(|seat| seat.player.player_id == player_id && seat.bid == Some(seat.tricks_won))
+    };
+    let mut round_points = Vec::new();
+    for seat in &table.seats {
+        let mut points = round_score(
+            seat.bid.unwrap_or(0),
+            seat.tricks_won,
+            seat.bonus,
+            table.round,
+        );
+        if made_bid(seat.player.player_id) {
+            let allies_made = seat.alliances.iter().filter(|&&ally| made_bid(ally));
+            points += LOOT_BONUS * allies_made.count() as i32;
+        }
+        round_points.push((seat.player.player_id, points));
+    }
+    for &(player_id, points) in &round_points {
+        table.scoreboard.record(player_id, points);
+    }
+    round_points
+}
+
+#[cfg(test)]
+#[test]
+fn test_round_score() {
+    use crate::game::{CardColor, ColorCard, MermaidCard, PirateCard, SkullCard, SkullKingCard};
+
+    assert_eq!(round_score(2, 2, 0, 5), 40);
+    assert_eq!(round_score(2, 2, 30, 5), 70);
+    assert_eq!(round_score(3, 1, 30, 5), -20);
+    assert_eq!(round_score(0, 0, 0, 7), 70);
+    assert_eq!(round_score(0, 1, 0, 7), -70);
+
+    let skull_king = CardType::SkullKing(SkullKingCard {});
+    let pirate = CardType::Pirate(PirateCard {});
+    let mermaid = CardType::Mermaid(MermaidCard {});
+    let red_14 = CardType::Color(ColorCard {
+        color: CardColor::Red,
+        value: 14,
+    });
+    let black_14 = CardType::Skull(SkullCard { value: 14 });
+
+    assert_eq!(
+        capture_bonus(&skull_king, &[pirate, skull_king, pirate], 14),
+        60
+    );
+    assert_eq!(
+        capture_bonus(&mermaid, &[skull_king, mermaid, red_14], 14),
+        50
+    );
+    assert_eq!(capture_bonus(&black_14, &[red_14, black_14], 14), 30);
+    // with cards up to 20 a 14 is nothing special
+    assert_eq!(capture_bonus(&black_14, &[red_14, black_14], 20), 0);
+
+    let mut scoreboard = Scoreboard::new();
+    scoreboard.record(1, 40);
+    scoreboard.record(2, -10);
+    scoreboard.record(1, -20);
+    assert_eq!(scoreboard.total(1), 20);
+    assert_eq!(scoreboard.players[0].rounds, vec![40, -20]);
+    assert_eq!(scoreboard.total(3), 0);
+}
@@ -0,0 +1,66 @@
+use rand::{distr::Alphanumeric, rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::GameError, lifecycle::Game};
+
+const TOKEN_LENGTH: usize = 32;
+
+/// Secret handed to a player when they take a seat; every action on that
+/// seat has to present it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSession {
+    pub player_id: i32,
+    pub token: String,
+}
+
+fn new_token() -> String {
+    rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+impl Game {
+    /// Seats a new player and returns their id with the session token.
+    pub fn join(&mut self, name: String) -> Result<(i32, String), GameError> {
+        let player_id = self.add_player(name)?;
+        let token = new_token();
+        self.sessions.push(PlayerSession {
+            player_id,
+            token: token.clone(),
+        });
+        Ok((player_id, token))
+    }
+
+    /// The player owning `token` at this table.
+    pub fn authenticate(&self, token: &str) -> Result<i32, GameError> {
+        self.sessions
+            .iter()
+            .find(|session| session.token == token)
+            .map(|session| session.player_id)
+            .ok_or(GameError::Unauthorized)
+    }
+
+    pub(crate) fn close_session(&mut self, player_id: i32) {
+        self.sessions
+            .retain(|session| session.player_id != player_id);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_sessions() {
+    let mut game = Game::new();
+    let (anne, anne_token) = game.join("anne".to_string()).unwrap();
+    let (bart, bart_token) = game.join("bart".to_string()).unwrap();
+    assert_ne!(anne_token, bart_token);
+    assert_eq!(anne_token.len(), TOKEN_LENGTH);
+
+    assert_eq!(game.authenticate(&anne_token), Ok(anne));
+    assert_eq!(game.authenticate(&bart_token), Ok(bart));
+    assert_eq!(game.authenticate("guess"), Err(GameError::Unauthorized));
+
+    game.kick(anne, bart).unwrap();
+    assert_eq!(game.authenticate(&bart_token), Err(GameError::Unauthorized));
+}
@@ -0,0 +1,170 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+
+use crate::{
+    bot::BotKind,
+    error::GameError,
+    lifecycle::{Game, GamePhase},
+    lobby::{RuleOptions, TableSettings},
+};
+
+/// Bot-only games to play, one strategy per seat.
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    pub games: usize,
+    pub seats: Vec<BotKind>,
+    pub rules: RuleOptions,
+    pub seed: u64,
+}
+
+/// Results of every game for one seat.
+#[derive(Debug, Clone)]
+pub struct SeatStats {
+    pub kind: BotKind,
+    pub wins: usize,
+    pub final_scores: Vec<i32>,
+    pub exact_bids: usize,
+    pub rounds: usize,
+}
+
+impl SeatStats {
+    fn new(kind: BotKind) -> Self {
+        SeatStats {
+            kind,
+            wins: 0,
+            final_scores: Vec::new(),
+            exact_bids: 0,
+            rounds: 0,
+        }
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.final_scores.len().max(1) as f64
+    }
+
+    pub fn average_score(&self) -> f64 {
+        self.final_scores.iter().sum::<i32>() as f64 / self.final_scores.len().max(1) as f64
+    }
+
+    pub fn score_variance(&self) -> f64 {
+        let average = self.average_score();
+        self.final_scores
+            .iter()
+            .map(|&score| (score as f64 - average).powi(2))
+            .sum::<f64>()
+            / self.final_scores.len().max(1) as f64
+    }
+
+    pub fn bid_accuracy(&self) -> f64 {
+        self.exact_bids as f64 / self.rounds.max(1) as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    pub games: usize,
+    pub seats: Vec<SeatStats>,
+}
+
+/// Plays one complete game between bots and returns it finished.
+pub fn play_game(
+    seats: &[BotKind],
+    rules: &RuleOptions,
+    rng: &mut ChaCha8Rng,
+    mut after_round: impl FnMut(&Game),
+) -> Result<Game, GameError> {
+    let settings = TableSettings {
+        name: "simulation".to_string(),
+        max_seats: seats.len(),
+        rules: rules.clone(),
+    };
+    let mut game = Game::with_settings(settings)?;
+    for &kind in seats {
+        game.add_bot(kind)?;
+    }
+    game.start_with_seed(rng.random())?;
+    while game.phase != GamePhase::Finished {
+        game.run_bots(rng)?;
+        if game.phase != GamePhase::RoundScoring {
+            return Err(GameError::WrongPhase);
+        }
+        after_round(&game);
+        game.next_round()?;
+    }
+    Ok(game)
+}
+
+pub fn run(config: &SimulationConfig) -> Result<SimulationReport, GameError> {
+    // ChaCha8 plays the same games for a seed on every build and platform
+    let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+    let mut seats: Vec<SeatStats> = config
+        .seats
+        .iter()
+        .map(|&kind| SeatStats::new(kind))
+        .collect();
+
+    for _ in 0..config.games {
+        let game = play_game(&config.seats, &config.rules, &mut rng, |game| {
+            for (index, stats) in seats.iter_mut().enumerate() {
+                // bots join in order, so seat n holds player n + 1
+                let player_id = index as i32 + 1;
+                if let Some(seat) = game.seat_of(player_id).map(|seat| &game.table.seats[seat]) {
+                    stats.rounds += 1;
+                    if seat.bid == Some(seat.tricks_won) {
+                        stats.exact_bids += 1;
+                    }
+                }
+            }
+        })?;
+
+        let totals: Vec<i32> = (1..=seats.len() as i32)
+            .map(|player_id| game.table.scoreboard.total(player_id))
+            .collect();
+        let best = totals.iter().copied().max().unwrap_or(0);
+        for (stats, total) in seats.iter_mut().zip(totals) {
+            stats.final_scores.push(total);
+            if total == best {
+                stats.wins += 1;
+            }
+        }
+    }
+
+    Ok(SimulationReport {
+        games: config.games,
+        seats,
+    })
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} games", self.games)?;
+        for (index, stats) in self.seats.iter().enumerate() {
+            writeln!(f, "Seat {} ({:?}):", index + 1, stats.kind)?;
+            writeln!(f, "\twin rate: {:.1}%", stats.win_rate() * 100.0)?;
+            writeln!(f, "\taverage score: {:.1}", stats.average_score())?;
+            writeln!(f, "\tscore variance: {:.1}", stats.score_variance())?;
+            writeln!(f, "\tbid accuracy: {:.1}%", stats.bid_accuracy() * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_simulation_is_reproducible() {
+    let config = SimulationConfig {
+        games: 5,
+        seats: vec![BotKind::Random, BotKind::Heuristic, BotKind::Heuristic],
+        rules: RuleOptions::new(),
+        seed: 7,
+    };
+    let report = run(&config).unwrap();
+    assert_eq!(report.seats.len(), 3);
+    for stats in &report.seats {
+        assert_eq!(stats.final_scores.len(), 5);
+        assert_eq!(stats.rounds, 50);
+    }
+    assert!(report.seats.iter().map(|stats| stats.wins).sum::<usize>() >= 5);
+    assert_eq!(format!("{}", report), format!("{}", run(&config).unwrap()));
+}
@@ -0,0 +1,66 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::lifecycle::Game;
+
+const EXTENSION: &str = "json";
+
+/// Keeps one JSON snapshot per table in a directory so games survive a
+/// restart of the server.
+#[derive(Debug, Clone)]
+pub struct TableStore {
+    dir: PathBuf,
+}
+
+impl TableStore {
+    pub fn new(dir: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(TableStore {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    fn path(&self, table_id: &str) -> PathBuf {
+        self.dir.join(table_id).with_extension(EXTENSION)
+    }
+
+    /// Writes the snapshot next to the previous one then swaps them, so a
+    /// crash mid-write never leaves a truncated game behind.
+    pub fn save(&self, table_id: &str, game: &Game) -> io::Result<()> {
+        let json = serde_json::to_string(game).map_err(io::Error::other)?;
+        let path = self.path(table_id);
+        let partial = path.with_extension("partial");
+        fs::write(&partial, json)?;
+        fs::rename(partial, path)
+    }
+
+    /// Every saved game by table id. Finished games are loaded too and their
+    /// snapshots kept, so their log and replay can still be reviewed after a
+    /// restart. A snapshot that cannot be read is reported and left out, the
+    /// other tables still load.
+    pub fn load_all(&self) -> io::Result<Vec<(String, Game)>> {
+        let mut games = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+            let Some(table_id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let game: Game = match fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|json| serde_json::from_str(&json).map_err(|error| error.to_string()))
+            {
+                Ok(game) => game,
+                Err(error) => {
+                    eprintln!("skipping saved table {}: {}", table_id, error);
+                    continue;
+                }
+            };
+            games.push((table_id.to_string(), game));
+        }
+        Ok(games)
+    }
+}
@@ -0,0 +1,163 @@
+use serde::Serialize;
+use std::fmt;
+
+use crate::{
+    error::GameError,
+    game::PlayCard,
+    lifecycle::{Game, GamePhase},
+    online_board::{self, PlayedCard},
+};
+
+/// What everyone is allowed to see of a seat.
+#[derive(Debug, Clone, Serialize)]
+pub struct SeatView {
+    pub player_id: i32,
+    pub name: String,
+    pub cards_in_hand: usize,
+    pub tricks_won: i32,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BidView {
+    pub player_id: i32,
+    pub bid: i32,
+}
+
+/// The public side of a game: never the deck nor anybody's hand.
+#[derive(Debug, Clone, Serialize)]
+pub struct TableView {
+    pub phase: GamePhase,
+    pub round: i32,
+    pub river: Vec<PlayedCard>,
+    pub bids: Option<Vec<BidView>>,
+    pub next_player_id: Option<i32>,
+    pub seats: Vec<SeatView>,
+    /// Highest number on the cards of this table's deck.
+    pub highest_value: i32,
+}
+
+/// A game as seen from one seat: their own hand on top of the public view.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerView {
+    pub player_id: i32,
+    pub hand: Vec<PlayCard>,
+    #[serde(flatten)]
+    pub table: TableView,
+}
+
+impl Game {
+    pub fn table_view(&self) -> TableView {
+        let table = &self.table;
+
+        let mut river = table.river.clone();
+        river.sort_by_key(|played_card| played_card.order());
+        let next_player_id = match self.phase {
+            GamePhase::Playing => Some(table.seats[self.current_seat()].player.player_id),
+            _ => None,
+        };
+        let bids = online_board::revealed_bids(table).map(|bids| {
+            bids.into_iter()
+                .map(|(player_id, bid)| BidView { player_id, bid })
+                .collect()
+        });
+
+        TableView {
+            phase: self.phase,
+            round: table.round,
+            river,
+            bids,
+            next_player_id,
+            seats: table
+                .seats
+                .iter()
+                .map(|seat| SeatView {
+                    player_id: seat.player.player_id,
+                    name: seat.player.name.clone(),
+                    cards_in_hand: seat.hand.len(),
+                    tricks_won: seat.tricks_won,
+                    score: table.scoreboard.total(seat.player.player_id),
+                })
+                .collect(),
+            highest_value: self.settings.rules.highest_value(),
+        }
+    }
+
+    pub fn player_view(&self, player_id: i32) -> Result<PlayerView, GameError> {
+        let seat_index = self.seat_of(player_id).ok_or(GameError::UnknownPlayer)?;
+        Ok(PlayerView {
+            player_id,
+            hand: self.table.seats[seat_index].hand.clone(),
+            table: self.table_view(),
+        })
+    }
+}
+
+impl fmt::Display for TableView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Round {} ({:?})", self.round, self.phase)?;
+        if let Some(next_player_id) = self.next_player_id {
+            writeln!(f, "Next to play: player {}", next_player_id)?;
+        }
+        writeln!(f, "Seats:")?;
+        for seat in &self.seats {
+            writeln!(
+                f,
+                "\t{} (player {}): {} cards, {} tricks, {} points",
+                seat.name, seat.player_id, seat.cards_in_hand, seat.tricks_won, seat.score
+            )?;
+        }
+        match &self.bids {
+            Some(bids) => {
+                writeln!(f, "Bids:")?;
+                for bid in bids {
+                    writeln!(f, "\tplayer {}: {}", bid.player_id, bid.bid)?;
+                }
+            }
+            None => writeln!(f, "Bids: hidden")?,
+        }
+        writeln!(f, "River {} cards:", self.river.len())?;
+        for played_card in &self.river {
+            write!(
+                f,
+                "\tplayer {}: {}",
+                played_card.player_id(),
+                played_card.card()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PlayerView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.table)?;
+        writeln!(f, "Your hand: {} cards", self.hand.len())?;
+        for (index, card) in self.hand.iter().enumerate() {
+            write!(f, "\t{}: {}", index, card)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_player_view_hides_other_hands() {
+    let mut game = Game::new();
+    game.seat_players(2);
+    game.start().unwrap();
+
+    assert_eq!(game.player_view(9).err(), Some(GameError::UnknownPlayer));
+    let view = game.player_view(1).unwrap();
+    let seat = &game.table.seats[game.seat_of(1).unwrap()];
+    assert_eq!(view.hand.len(), 1);
+    assert_eq!(format!("{}", view.hand[0]), format!("{}", seat.hand[0]));
+    assert!(view.table.seats.iter().all(|seat| seat.cards_in_hand == 1));
+    assert!(view.table.bids.is_none());
+
+    game.bid(1, 0).unwrap();
+    game.bid(2, 1).unwrap();
+    let view = game.player_view(2).unwrap();
+    assert_eq!(view.table.bids.map(|bids| bids.len()), Some(2));
+    assert!(view.table.next_player_id.is_some());
+}